@@ -0,0 +1,133 @@
+//! Atomic file writes via temp file + rename
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `contents` to `path` atomically.
+///
+/// The data is written to a temporary file in the same directory and then
+/// renamed over the target, so readers never observe a partially written
+/// file. Permissions of an existing target are preserved (and on Unix its
+/// owner, when privileged), and symlinks are resolved so the link itself is
+/// not replaced.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let target = resolve_target(path)?;
+    let temp = temp_path(&target);
+
+    let result = write_temp(&target, &temp, contents).and_then(|()| fs::rename(&temp, &target));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn resolve_target(path: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(path),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+fn temp_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{name}.fini-tmp-{}", std::process::id()))
+}
+
+fn write_temp(target: &Path, temp: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+
+    if let Ok(meta) = fs::metadata(target) {
+        // Before the mode: chown clears setuid/setgid bits
+        #[cfg(unix)]
+        restore_owner(temp, &meta);
+        fs::set_permissions(temp, meta.permissions())?;
+    }
+    Ok(())
+}
+
+/// Give `temp` the owner and group of the target described by `meta`.
+///
+/// Best-effort: without privileges the temp file keeps the current user.
+#[cfg(unix)]
+fn restore_owner(temp: &Path, meta: &fs::Metadata) {
+    use std::os::unix::fs::{chown, MetadataExt};
+
+    let _ = chown(temp, Some(meta.uid()), Some(meta.gid()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_creates_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("new.txt");
+
+        write_atomic(&path, b"hello\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello\n");
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_and_leaves_no_temp() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_preserves_permissions_and_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        let link = dir.path().join("link.sh");
+        symlink(&path, &link).unwrap();
+
+        write_atomic(&link, b"new\n").unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_preserves_owner_when_privileged() {
+        use std::os::unix::fs::{chown, MetadataExt};
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("shared.txt");
+        fs::write(&path, "old").unwrap();
+        // Handing a file to another user needs privileges
+        if chown(&path, Some(4242), Some(4242)).is_err() {
+            return;
+        }
+
+        write_atomic(&path, b"new\n").unwrap();
+
+        let meta = fs::metadata(&path).unwrap();
+        assert_eq!((meta.uid(), meta.gid()), (4242, 4242));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+    }
+}
@@ -5,6 +5,7 @@ use std::io;
 use std::path::{Path, PathBuf};
 
 use super::file::find_file_upward;
+use crate::normalize::NormalizeConfig;
 
 /// Relevant settings extracted from .editorconfig
 #[derive(Debug, Default)]
@@ -12,6 +13,8 @@ pub struct EditorConfigSettings {
     pub trim_trailing_whitespace: Option<bool>,
     pub insert_final_newline: Option<bool>,
     pub end_of_line: Option<String>,
+    /// Non-standard but widely supported; `off` leaves it unset
+    pub max_line_length: Option<usize>,
 }
 
 /// Find .editorconfig by searching upward from the given directory.
@@ -62,6 +65,9 @@ pub fn parse_editorconfig(path: &Path) -> io::Result<EditorConfigSettings> {
                 "end_of_line" => {
                     settings.end_of_line = Some(value);
                 }
+                "max_line_length" => {
+                    settings.max_line_length = value.parse().ok();
+                }
                 _ => {}
             }
         }
@@ -97,6 +103,13 @@ pub fn check_editorconfig_conflicts(settings: &EditorConfigSettings) -> Vec<Stri
     warnings
 }
 
+/// Adopt .editorconfig settings for options that fini.toml and the CLI leave unset.
+pub fn apply_editorconfig_defaults(settings: &EditorConfigSettings, config: &mut NormalizeConfig) {
+    if config.max_line_length.is_none() {
+        config.max_line_length = settings.max_line_length;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +162,43 @@ trim_trailing_whitespace = false
         assert_eq!(settings.trim_trailing_whitespace, Some(true));
         assert_eq!(settings.insert_final_newline, Some(true));
         assert_eq!(settings.end_of_line, Some("lf".to_string()));
+        assert_eq!(settings.max_line_length, None);
+    }
+
+    #[test]
+    fn test_parse_editorconfig_max_line_length() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join(".editorconfig");
+        fs::write(&config_path, "[*]\nmax_line_length = 100\n").unwrap();
+        assert_eq!(
+            parse_editorconfig(&config_path).unwrap().max_line_length,
+            Some(100)
+        );
+
+        fs::write(&config_path, "[*]\nmax_line_length = off\n").unwrap();
+        assert_eq!(
+            parse_editorconfig(&config_path).unwrap().max_line_length,
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_editorconfig_defaults_keeps_explicit_limit() {
+        let settings = EditorConfigSettings {
+            max_line_length: Some(100),
+            ..EditorConfigSettings::default()
+        };
+
+        let mut config = NormalizeConfig::default();
+        apply_editorconfig_defaults(&settings, &mut config);
+        assert_eq!(config.max_line_length, Some(100));
+
+        let mut config = NormalizeConfig {
+            max_line_length: Some(120),
+            ..NormalizeConfig::default()
+        };
+        apply_editorconfig_defaults(&settings, &mut config);
+        assert_eq!(config.max_line_length, Some(120));
     }
 
     #[test]
@@ -177,6 +227,7 @@ indent_style = space
             trim_trailing_whitespace: Some(true),
             insert_final_newline: Some(true),
             end_of_line: Some("lf".to_string()),
+            max_line_length: None,
         };
 
         let warnings = check_editorconfig_conflicts(&settings);
@@ -189,6 +240,7 @@ indent_style = space
             trim_trailing_whitespace: Some(false),
             insert_final_newline: Some(false),
             end_of_line: Some("crlf".to_string()),
+            max_line_length: Some(100),
         };
 
         let warnings = check_editorconfig_conflicts(&settings);
@@ -204,6 +256,7 @@ indent_style = space
             trim_trailing_whitespace: None,
             insert_final_newline: Some(false),
             end_of_line: None,
+            max_line_length: None,
         };
 
         let warnings = check_editorconfig_conflicts(&settings);
@@ -5,22 +5,40 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-use super::toml_schema::FiniToml;
+use super::toml_schema::{FiniToml, NormalizeSection};
+use crate::normalize::{validate_rule_order, DETECTION_RULE_IDS, RULE_IDS};
 
 /// Error type for configuration loading
 #[derive(Debug)]
 pub enum ConfigError {
     /// IO error reading the file
     Io(io::Error),
-    /// TOML parsing error
-    Parse(toml::de::Error),
+    /// TOML parsing error, with the 1-based line and column where it occurred (if known)
+    Parse {
+        source: toml::de::Error,
+        position: Option<(usize, usize)>,
+    },
+    /// Well-formed TOML with an invalid value
+    Invalid(String),
 }
 
 impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ConfigError::Io(e) => write!(f, "failed to read config file: {e}"),
-            ConfigError::Parse(e) => write!(f, "failed to parse config file: {e}"),
+            ConfigError::Parse {
+                source,
+                position: Some((line, column)),
+            } => write!(
+                f,
+                "failed to parse config file at line {line}, column {column}: {}",
+                source.message()
+            ),
+            ConfigError::Parse {
+                source,
+                position: None,
+            } => write!(f, "failed to parse config file: {}", source.message()),
+            ConfigError::Invalid(msg) => write!(f, "invalid config file: {msg}"),
         }
     }
 }
@@ -29,7 +47,8 @@ impl std::error::Error for ConfigError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             ConfigError::Io(e) => Some(e),
-            ConfigError::Parse(e) => Some(e),
+            ConfigError::Parse { source, .. } => Some(source),
+            ConfigError::Invalid(_) => None,
         }
     }
 }
@@ -40,12 +59,22 @@ impl From<io::Error> for ConfigError {
     }
 }
 
-impl From<toml::de::Error> for ConfigError {
-    fn from(e: toml::de::Error) -> Self {
-        ConfigError::Parse(e)
+impl ConfigError {
+    /// Wrap a TOML error, locating its span in `content`
+    fn parse(source: toml::de::Error, content: &str) -> Self {
+        let position = source.span().map(|span| line_column(content, span.start));
+        ConfigError::Parse { source, position }
     }
 }
 
+/// 1-based line and column (in characters) of byte `offset` in `content`
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    (line, before[line_start..].chars().count() + 1)
+}
+
 /// Search upward from `start_dir` for a file with the given name.
 ///
 /// If `stop_at_git_root` is true, stops searching when a `.git` directory is found.
@@ -54,13 +83,25 @@ pub fn find_file_upward(
     start_dir: &Path,
     filename: &str,
     stop_at_git_root: bool,
+) -> Option<PathBuf> {
+    find_first_upward(start_dir, &[filename], stop_at_git_root)
+}
+
+/// Like `find_file_upward`, but checks several relative paths in each directory,
+/// returning the first that exists (earlier entries win within a directory).
+fn find_first_upward(
+    start_dir: &Path,
+    candidates: &[&str],
+    stop_at_git_root: bool,
 ) -> Option<PathBuf> {
     let mut current = start_dir.to_path_buf();
 
     loop {
-        let file_path = current.join(filename);
-        if file_path.exists() {
-            return Some(file_path);
+        for candidate in candidates {
+            let file_path = current.join(candidate);
+            if file_path.exists() {
+                return Some(file_path);
+            }
         }
 
         if stop_at_git_root && current.join(".git").exists() {
@@ -73,26 +114,74 @@ pub fn find_file_upward(
     }
 }
 
+/// Config file locations checked in each directory, in order of preference
+const CONFIG_CANDIDATES: &[&str] = &["fini.toml", ".config/fini.toml"];
+
 /// Find fini.toml by searching upward from the given directory.
 ///
-/// Stops at the first `fini.toml` found, or at the git repository root
+/// Each directory is checked for `fini.toml`, then `.config/fini.toml`.
+/// Stops at the first config found, or at the git repository root
 /// (directory containing `.git`), whichever comes first.
 ///
 /// Returns `None` if no config file is found.
 pub fn find_config_file(start_dir: &Path) -> Option<PathBuf> {
-    find_file_upward(start_dir, "fini.toml", true)
+    find_first_upward(start_dir, CONFIG_CANDIDATES, true)
 }
 
 /// Load and parse fini.toml from the given path.
 pub fn load_config(path: &Path) -> Result<FiniToml, ConfigError> {
+    load_config_with_unknown_keys(path).map(|(config, _)| config)
+}
+
+/// Load and parse fini.toml, also returning keys it doesn't recognize
+/// (dotted paths like `normalize.fix_code_block`) so typos can be reported.
+pub fn load_config_with_unknown_keys(path: &Path) -> Result<(FiniToml, Vec<String>), ConfigError> {
     let content = fs::read_to_string(path)?;
-    let config: FiniToml = toml::from_str(&content)?;
-    Ok(config)
+    let mut unknown_keys = vec![];
+    let config: FiniToml = serde_ignored::deserialize(toml::Deserializer::new(&content), |key| {
+        unknown_keys.push(key.to_string())
+    })
+    .map_err(|e| ConfigError::parse(e, &content))?;
+    validate_section(&config.normalize, "")?;
+    for (name, profile) in &config.profiles {
+        validate_section(profile, &format!("profiles.{name}."))?;
+    }
+    if let Some(rule_id) = config
+        .rules
+        .keys()
+        .find(|id| !DETECTION_RULE_IDS.contains(&id.as_str()) && !RULE_IDS.contains(&id.as_str()))
+    {
+        return Err(ConfigError::Invalid(format!(
+            "rules: unknown rule `{rule_id}` (expected a detection: {}; or a fixing rule: {})",
+            DETECTION_RULE_IDS.join(", "),
+            RULE_IDS.join(", ")
+        )));
+    }
+    Ok((config, unknown_keys))
+}
+
+/// Check the values of a `[normalize]` or `[profiles.<name>]` table; `prefix`
+/// is prepended to the key names in errors.
+fn validate_section(section: &NormalizeSection, prefix: &str) -> Result<(), ConfigError> {
+    if let Some(order) = &section.rule_order {
+        validate_rule_order(order)
+            .map_err(|e| ConfigError::Invalid(format!("{prefix}rule_order: {e}")))?;
+    }
+    // Every line contains the empty string, so a blank entry would allow all secrets
+    if let Some(allowlist) = &section.secret_allowlist {
+        if allowlist.iter().any(|entry| entry.trim().is_empty()) {
+            return Err(ConfigError::Invalid(format!(
+                "{prefix}secret_allowlist: entries must not be empty"
+            )));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::options::NORMALIZE_OPTIONS;
     use std::fs;
     use tempfile::TempDir;
 
@@ -119,6 +208,34 @@ mod tests {
         assert_eq!(found, Some(config_path));
     }
 
+    #[test]
+    fn test_find_config_in_dot_config_dir() {
+        let parent = TempDir::new().unwrap();
+        fs::create_dir(parent.path().join(".config")).unwrap();
+        let config_path = parent.path().join(".config").join("fini.toml");
+        fs::write(&config_path, "[normalize]\n").unwrap();
+
+        let child = parent.path().join("subdir");
+        fs::create_dir(&child).unwrap();
+
+        assert_eq!(find_config_file(&child), Some(config_path));
+    }
+
+    #[test]
+    fn test_find_config_prefers_top_level_over_dot_config() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".config")).unwrap();
+        fs::write(
+            dir.path().join(".config").join("fini.toml"),
+            "[normalize]\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(&config_path, "[normalize]\n").unwrap();
+
+        assert_eq!(find_config_file(dir.path()), Some(config_path));
+    }
+
     #[test]
     fn test_find_config_stops_at_git_root() {
         let dir = TempDir::new().unwrap();
@@ -171,6 +288,33 @@ fix_code_blocks = true
         assert_eq!(config.normalize.fix_code_blocks, Some(true));
     }
 
+    #[test]
+    fn test_load_config_detection_options() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(
+            &config_path,
+            r#"
+[normalize]
+detect_todos = false
+detect_fixmes = false
+detect_debug = false
+strict_debug = true
+detect_secrets = false
+max_line_length = 100
+"#,
+        )
+        .unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.normalize.detect_todos, Some(false));
+        assert_eq!(config.normalize.detect_fixmes, Some(false));
+        assert_eq!(config.normalize.detect_debug, Some(false));
+        assert_eq!(config.normalize.strict_debug, Some(true));
+        assert_eq!(config.normalize.detect_secrets, Some(false));
+        assert_eq!(config.normalize.max_line_length, Some(100));
+    }
+
     #[test]
     fn test_load_config_partial() {
         let dir = TempDir::new().unwrap();
@@ -209,6 +353,154 @@ fix_code_blocks = true
         fs::write(&config_path, "invalid toml {{{\n").unwrap();
 
         let result = load_config(&config_path);
-        assert!(matches!(result, Err(ConfigError::Parse(_))));
+        assert!(matches!(result, Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_parse_error_reports_line_and_column() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(
+            &config_path,
+            "[normalize]\nremove_zero_width = true\n\nmax_blank_lines = 2\ndetect_todos = yes\n",
+        )
+        .unwrap();
+
+        let err = load_config(&config_path).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Parse {
+                position: Some((5, 16)),
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("failed to parse config file at line 5, column 16: "));
+    }
+
+    #[test]
+    fn test_type_error_reports_line() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(&config_path, "[normalize]\n\nmax_blank_lines = \"two\"\n").unwrap();
+
+        let err = load_config(&config_path).unwrap_err();
+        assert!(err.to_string().contains("at line 3"), "{err}");
+    }
+
+    #[test]
+    fn test_load_config_rejects_invalid_rule_order() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(
+            &config_path,
+            "[normalize]\nrule_order = [\"eof-newline\", \"line-endings\"]\n",
+        )
+        .unwrap();
+
+        let err = load_config(&config_path).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+        assert!(err.to_string().contains("rule_order"));
+    }
+
+    #[test]
+    fn test_load_config_accepts_kebab_case_keys() {
+        let dir = TempDir::new().unwrap();
+        let snake_path = dir.path().join("snake.toml");
+        let kebab_path = dir.path().join("kebab.toml");
+        let assignments: Vec<(&str, &str)> = NORMALIZE_OPTIONS
+            .iter()
+            .map(|option| (option.key, option.example))
+            .collect();
+        let write = |path: &Path, kebab: bool| {
+            let mut toml = String::from("[normalize]\n");
+            for (key, example) in &assignments {
+                let key = if kebab {
+                    key.replace('_', "-")
+                } else {
+                    key.to_string()
+                };
+                toml.push_str(&format!("{key} = {example}\n"));
+            }
+            fs::write(path, toml).unwrap();
+        };
+        write(&snake_path, false);
+        write(&kebab_path, true);
+
+        let (snake, _) = load_config_with_unknown_keys(&snake_path).unwrap();
+        let (kebab, unknown) = load_config_with_unknown_keys(&kebab_path).unwrap();
+        assert!(unknown.is_empty(), "unknown keys: {unknown:?}");
+        assert_eq!(
+            serde_json::to_value(&kebab.normalize).unwrap(),
+            serde_json::to_value(&snake.normalize).unwrap()
+        );
+        assert_eq!(kebab.normalize.max_blank_lines, Some(2));
+    }
+
+    #[test]
+    fn test_load_config_rules_sections() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(
+            &config_path,
+            "[rules.secret-pattern]\nexclude = [\"*.test.js\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        let scope = &config.rules["secret-pattern"];
+        assert_eq!(
+            scope.exclude.as_deref(),
+            Some(&["*.test.js".to_string()][..])
+        );
+        assert!(scope.include.is_none());
+
+        // Fixing rules can be scoped too
+        fs::write(&config_path, "[rules.eof-newline]\nexclude = [\"*.csv\"]\n").unwrap();
+        assert!(load_config(&config_path)
+            .unwrap()
+            .rules
+            .contains_key("eof-newline"));
+
+        fs::write(&config_path, "[rules.no-such-rule]\nexclude = [\"*.md\"]\n").unwrap();
+        let err = load_config(&config_path).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+        assert!(err.to_string().contains("no-such-rule"));
+    }
+
+    #[test]
+    fn test_load_config_rejects_empty_secret_allowlist_entry() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+
+        fs::write(
+            &config_path,
+            "[normalize]\nsecret_allowlist = [\"demo\", \"\"]\n",
+        )
+        .unwrap();
+        let err = load_config(&config_path).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+        assert!(err.to_string().contains("secret_allowlist"));
+
+        fs::write(&config_path, "[profiles.ci]\nsecret_allowlist = [\" \"]\n").unwrap();
+        let err = load_config(&config_path).unwrap_err();
+        assert!(err.to_string().contains("profiles.ci.secret_allowlist"));
+    }
+
+    #[test]
+    fn test_load_config_reports_unknown_keys() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("fini.toml");
+        fs::write(
+            &config_path,
+            "[normalize]\nfix_code_block = true\nmax_blank_lines = 1\n\n[extra]\nkey = 1\n",
+        )
+        .unwrap();
+
+        let (config, unknown) = load_config_with_unknown_keys(&config_path).unwrap();
+
+        assert_eq!(config.normalize.max_blank_lines, Some(1));
+        assert_eq!(unknown, ["normalize.fix_code_block", "extra"]);
     }
 }
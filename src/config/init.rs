@@ -4,6 +4,8 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use super::options::SECTIONS;
+
 /// Template fini.toml with documentation
 pub const FINI_TOML_TEMPLATE: &str = r#"# fini.toml - Configuration for fini file normalizer
 # https://github.com/tsukasaI/fini
@@ -31,16 +33,50 @@ pub const FINI_TOML_TEMPLATE: &str = r#"# fini.toml - Configuration for fini fil
 # Default: true
 # remove_leading_blanks = true
 
+# Remove blank lines between the last line of content and the end of the file.
+# Default: true
+# trim_trailing_blank_lines = true
+
 # Remove markdown code block markers (``` fences).
 # Enable when extracting code from AI assistant responses.
 # Default: false
 # fix_code_blocks = false
 "#;
 
+/// Header for the full template generated by `--init --full`
+const FULL_TEMPLATE_HEADER: &str = r#"# fini.toml - Full configuration reference for fini file normalizer
+# https://github.com/tsukasaI/fini
+#
+# Every available option is listed below with its default value.
+# Uncomment and modify as needed.
+"#;
+
+/// Build the full fini.toml template documenting every option.
+pub fn full_init_template() -> String {
+    let mut template = String::from(FULL_TEMPLATE_HEADER);
+
+    for section in SECTIONS {
+        template.push_str(&format!("\n[{}]\n", section.name));
+
+        for (idx, option) in section.options.iter().enumerate() {
+            if idx > 0 {
+                template.push('\n');
+            }
+            template.push_str(&format!(
+                "# {}\n# Default: {}\n# {} = {}\n",
+                option.description, option.default, option.key, option.example
+            ));
+        }
+    }
+
+    template
+}
+
 /// Generate fini.toml in the specified directory (or current directory if None).
 ///
+/// Writes the full template when `full` is true, the minimal one otherwise.
 /// Returns an error if fini.toml already exists.
-pub fn generate_init_file_in(dir: Option<&Path>) -> io::Result<PathBuf> {
+pub fn generate_init_file_in(dir: Option<&Path>, full: bool) -> io::Result<PathBuf> {
     let path = dir.map_or_else(|| PathBuf::from("fini.toml"), |d| d.join("fini.toml"));
 
     if path.exists() {
@@ -50,15 +86,19 @@ pub fn generate_init_file_in(dir: Option<&Path>) -> io::Result<PathBuf> {
         ));
     }
 
-    fs::write(&path, FINI_TOML_TEMPLATE)?;
+    if full {
+        fs::write(&path, full_init_template())?;
+    } else {
+        fs::write(&path, FINI_TOML_TEMPLATE)?;
+    }
     Ok(path)
 }
 
 /// Generate fini.toml in the current directory.
 ///
 /// Returns an error if fini.toml already exists.
-pub fn generate_init_file() -> io::Result<PathBuf> {
-    generate_init_file_in(None)
+pub fn generate_init_file(full: bool) -> io::Result<PathBuf> {
+    generate_init_file_in(None, full)
 }
 
 #[cfg(test)]
@@ -70,7 +110,7 @@ mod tests {
     fn test_generate_init_file_creates_file() {
         let dir = TempDir::new().unwrap();
 
-        let result = generate_init_file_in(Some(dir.path()));
+        let result = generate_init_file_in(Some(dir.path()), false);
         assert!(result.is_ok());
 
         let path = result.unwrap();
@@ -90,7 +130,7 @@ mod tests {
         // Create existing file
         fs::write(&config_path, "existing").unwrap();
 
-        let result = generate_init_file_in(Some(dir.path()));
+        let result = generate_init_file_in(Some(dir.path()), false);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
     }
@@ -102,4 +142,30 @@ mod tests {
             toml::from_str(FINI_TOML_TEMPLATE);
         assert!(parsed.is_ok());
     }
+
+    #[test]
+    fn test_full_template_is_valid_toml() {
+        let parsed: Result<super::super::toml_schema::FiniToml, _> =
+            toml::from_str(&full_init_template());
+        assert!(parsed.is_ok());
+    }
+
+    #[test]
+    fn test_full_template_mentions_detections() {
+        let template = full_init_template();
+        assert!(template.contains("[normalize]"));
+        assert!(template.contains("# detect_secrets = true"));
+        assert!(template.contains("# max_line_length = 120"));
+        assert!(template.contains("[files]"));
+        assert!(template.contains("# skip_filenames = ["));
+    }
+
+    #[test]
+    fn test_generate_full_init_file() {
+        let dir = TempDir::new().unwrap();
+
+        let path = generate_init_file_in(Some(dir.path()), true).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("detect_secrets"));
+    }
 }
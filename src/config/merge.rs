@@ -2,9 +2,14 @@
 //!
 //! Priority: CLI args > fini.toml > defaults
 
-use crate::NormalizeConfig;
+use std::borrow::Cow;
 
-use super::toml_schema::NormalizeSection;
+use crate::{
+    complete_rule_order, LineLengthMode, NormalizeConfig, Rule, DEFAULT_BINARY_CHECK_BYTES,
+};
+
+use super::file::ConfigError;
+use super::toml_schema::{FilesSection, FiniToml, NormalizeSection};
 
 /// CLI options that can override config file settings.
 ///
@@ -17,6 +22,8 @@ pub struct CliNormalizeOptions {
     /// If Some(true), keep leading blanks (inverted in config)
     pub keep_leading_blanks: Option<bool>,
     pub fix_code_blocks: Option<bool>,
+    /// If Some(true), report full-width spaces without replacing them (inverted in config)
+    pub no_fix_fullwidth: Option<bool>,
     // Phase 3: Human Error Prevention
     /// If Some(true), skip TODO detection
     pub no_detect_todos: Option<bool>,
@@ -30,6 +37,135 @@ pub struct CliNormalizeOptions {
     pub no_detect_secrets: Option<bool>,
     /// Maximum line length
     pub max_line_length: Option<usize>,
+    /// Indentation unit in spaces
+    pub indent_size: Option<usize>,
+    /// How line length is measured
+    pub line_length_mode: Option<LineLengthMode>,
+    /// If Some(true), keep trailing whitespace inside fenced code blocks
+    pub markdown_preserve_code: Option<bool>,
+    /// Rules allowed to modify content in this run (CLI only)
+    pub fix_kinds: Option<Vec<Rule>>,
+    /// If Some(true), keep trailing whitespace inside multi-line string literals
+    pub preserve_string_trailing_ws: Option<bool>,
+    /// If Some(true), replace curly quotes with straight ones
+    pub fix_smart_quotes: Option<bool>,
+    /// Report files with more than N lines
+    pub max_file_lines: Option<usize>,
+    /// If Some(true), report long base64 blobs in assignments
+    pub detect_base64: Option<bool>,
+    /// If Some(true), remove form feeds and vertical tabs
+    pub fix_form_feeds: Option<bool>,
+    /// If Some(true), use one space after list markers in markdown files
+    pub markdown_list_spacing: Option<bool>,
+    /// If Some(true), prepend the required header where it is missing
+    pub fix_header: Option<bool>,
+    /// If Some(true), keep trailing blank lines (inverted in config)
+    pub keep_trailing_blanks: Option<bool>,
+    /// If Some(true), move a #! line near the top to line 1
+    pub promote_shebang: Option<bool>,
+    /// If Some(true), only report TODO/FIXME comments without an issue reference
+    pub require_todo_reference: Option<bool>,
+}
+
+/// The `[normalize]` settings with the `[profiles.<name>]` table layered over
+/// them; keys the profile sets win. Without a profile, `[normalize]` as is.
+pub fn resolve_profile<'a>(
+    toml: &'a FiniToml,
+    profile: Option<&str>,
+) -> Result<Cow<'a, NormalizeSection>, ConfigError> {
+    let Some(name) = profile else {
+        return Ok(Cow::Borrowed(&toml.normalize));
+    };
+    let Some(overrides) = toml.profiles.get(name) else {
+        let available: Vec<&str> = toml.profiles.keys().map(String::as_str).collect();
+        return Err(ConfigError::Invalid(format!(
+            "unknown profile `{name}` (defined: {})",
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
+        )));
+    };
+
+    Ok(Cow::Owned(layer_sections(&toml.normalize, overrides)))
+}
+
+/// `base` with the keys `overrides` sets replaced by theirs
+fn layer_sections(base: &NormalizeSection, overrides: &NormalizeSection) -> NormalizeSection {
+    let base = base.clone();
+    // Destructured so a new key can't be left out of the layering
+    let NormalizeSection {
+        max_blank_lines,
+        remove_zero_width,
+        remove_leading_blanks,
+        fix_code_blocks,
+        detect_todos,
+        detect_fixmes,
+        detect_debug,
+        strict_debug,
+        detect_secrets,
+        max_line_length,
+        indent_size,
+        line_length_mode,
+        fix_fullwidth_space,
+        rule_order,
+        markdown_preserve_code,
+        require_shebang_exts,
+        preserve_string_trailing_ws,
+        normalize_smart_quotes,
+        normalize_dashes,
+        max_file_lines,
+        detect_base64_blobs,
+        base64_min_length,
+        base64_skip_data_uris,
+        normalize_form_feeds,
+        form_feed_replacement,
+        markdown_list_spacing,
+        secret_allowlist,
+        builtin_secret_allowlist,
+        required_header,
+        fix_header,
+        trim_trailing_blank_lines,
+        promote_shebang,
+        require_todo_reference,
+    } = overrides.clone();
+    NormalizeSection {
+        max_blank_lines: max_blank_lines.or(base.max_blank_lines),
+        remove_zero_width: remove_zero_width.or(base.remove_zero_width),
+        remove_leading_blanks: remove_leading_blanks.or(base.remove_leading_blanks),
+        fix_code_blocks: fix_code_blocks.or(base.fix_code_blocks),
+        detect_todos: detect_todos.or(base.detect_todos),
+        detect_fixmes: detect_fixmes.or(base.detect_fixmes),
+        detect_debug: detect_debug.or(base.detect_debug),
+        strict_debug: strict_debug.or(base.strict_debug),
+        detect_secrets: detect_secrets.or(base.detect_secrets),
+        max_line_length: max_line_length.or(base.max_line_length),
+        indent_size: indent_size.or(base.indent_size),
+        line_length_mode: line_length_mode.or(base.line_length_mode),
+        fix_fullwidth_space: fix_fullwidth_space.or(base.fix_fullwidth_space),
+        rule_order: rule_order.or(base.rule_order),
+        markdown_preserve_code: markdown_preserve_code.or(base.markdown_preserve_code),
+        require_shebang_exts: require_shebang_exts.or(base.require_shebang_exts),
+        preserve_string_trailing_ws: preserve_string_trailing_ws
+            .or(base.preserve_string_trailing_ws),
+        normalize_smart_quotes: normalize_smart_quotes.or(base.normalize_smart_quotes),
+        normalize_dashes: normalize_dashes.or(base.normalize_dashes),
+        max_file_lines: max_file_lines.or(base.max_file_lines),
+        detect_base64_blobs: detect_base64_blobs.or(base.detect_base64_blobs),
+        base64_min_length: base64_min_length.or(base.base64_min_length),
+        base64_skip_data_uris: base64_skip_data_uris.or(base.base64_skip_data_uris),
+        normalize_form_feeds: normalize_form_feeds.or(base.normalize_form_feeds),
+        form_feed_replacement: form_feed_replacement.or(base.form_feed_replacement),
+        markdown_list_spacing: markdown_list_spacing.or(base.markdown_list_spacing),
+        secret_allowlist: secret_allowlist.or(base.secret_allowlist),
+        builtin_secret_allowlist: builtin_secret_allowlist.or(base.builtin_secret_allowlist),
+        required_header: required_header.or(base.required_header),
+        fix_header: fix_header.or(base.fix_header),
+        trim_trailing_blank_lines: trim_trailing_blank_lines.or(base.trim_trailing_blank_lines),
+        promote_shebang: promote_shebang.or(base.promote_shebang),
+        require_todo_reference: require_todo_reference.or(base.require_todo_reference),
+    }
 }
 
 /// Merge configurations from CLI, TOML, and defaults.
@@ -89,7 +225,120 @@ pub fn merge_normalize_config(
             .max_line_length
             .or_else(|| toml.and_then(|t| t.max_line_length))
             .or(defaults.max_line_length),
+        indent_size: cli
+            .indent_size
+            .or_else(|| toml.and_then(|t| t.indent_size))
+            .or(defaults.indent_size),
+        line_length_mode: cli
+            .line_length_mode
+            .or_else(|| toml.and_then(|t| t.line_length_mode))
+            .unwrap_or(defaults.line_length_mode),
+        fix_fullwidth_space: cli
+            .no_fix_fullwidth
+            .map(|no| !no)
+            .or_else(|| toml.and_then(|t| t.fix_fullwidth_space))
+            .unwrap_or(defaults.fix_fullwidth_space),
+        rule_order: toml
+            .and_then(|t| t.rule_order.as_deref())
+            .map_or(defaults.rule_order, complete_rule_order),
+        markdown_preserve_code: cli
+            .markdown_preserve_code
+            .or_else(|| toml.and_then(|t| t.markdown_preserve_code))
+            .unwrap_or(defaults.markdown_preserve_code),
+        fix_kinds: cli.fix_kinds.clone().or(defaults.fix_kinds),
+        require_shebang_exts: toml
+            .and_then(|t| t.require_shebang_exts.clone())
+            .unwrap_or(defaults.require_shebang_exts),
+        preserve_string_trailing_ws: cli
+            .preserve_string_trailing_ws
+            .or_else(|| toml.and_then(|t| t.preserve_string_trailing_ws))
+            .unwrap_or(defaults.preserve_string_trailing_ws),
+        normalize_smart_quotes: cli
+            .fix_smart_quotes
+            .or_else(|| toml.and_then(|t| t.normalize_smart_quotes))
+            .unwrap_or(defaults.normalize_smart_quotes),
+        normalize_dashes: toml
+            .and_then(|t| t.normalize_dashes)
+            .unwrap_or(defaults.normalize_dashes),
+        max_file_lines: cli
+            .max_file_lines
+            .or_else(|| toml.and_then(|t| t.max_file_lines))
+            .or(defaults.max_file_lines),
+        detect_base64_blobs: cli
+            .detect_base64
+            .or_else(|| toml.and_then(|t| t.detect_base64_blobs))
+            .unwrap_or(defaults.detect_base64_blobs),
+        base64_min_length: toml
+            .and_then(|t| t.base64_min_length)
+            .unwrap_or(defaults.base64_min_length),
+        base64_skip_data_uris: toml
+            .and_then(|t| t.base64_skip_data_uris)
+            .unwrap_or(defaults.base64_skip_data_uris),
+        normalize_form_feeds: cli
+            .fix_form_feeds
+            .or_else(|| toml.and_then(|t| t.normalize_form_feeds))
+            .unwrap_or(defaults.normalize_form_feeds),
+        form_feed_replacement: toml
+            .and_then(|t| t.form_feed_replacement)
+            .unwrap_or(defaults.form_feed_replacement),
+        markdown_list_spacing: cli
+            .markdown_list_spacing
+            .or_else(|| toml.and_then(|t| t.markdown_list_spacing))
+            .unwrap_or(defaults.markdown_list_spacing),
+        secret_allowlist: toml
+            .and_then(|t| t.secret_allowlist.clone())
+            .unwrap_or(defaults.secret_allowlist),
+        builtin_secret_allowlist: toml
+            .and_then(|t| t.builtin_secret_allowlist)
+            .unwrap_or(defaults.builtin_secret_allowlist),
+        required_header: toml.and_then(|t| t.required_header.clone()),
+        fix_header: cli
+            .fix_header
+            .or_else(|| toml.and_then(|t| t.fix_header))
+            .unwrap_or(defaults.fix_header),
+        trim_trailing_blank_lines: cli
+            .keep_trailing_blanks
+            .map(|keep| !keep)
+            .or_else(|| toml.and_then(|t| t.trim_trailing_blank_lines))
+            .unwrap_or(defaults.trim_trailing_blank_lines),
+        promote_shebang: cli
+            .promote_shebang
+            .or_else(|| toml.and_then(|t| t.promote_shebang))
+            .unwrap_or(defaults.promote_shebang),
+        require_todo_reference: cli
+            .require_todo_reference
+            .or_else(|| toml.and_then(|t| t.require_todo_reference))
+            .unwrap_or(defaults.require_todo_reference),
+    }
+}
+
+/// Combine `--skip-file` names with `skip_filenames` from fini.toml.
+///
+/// Unlike other options, the CLI adds to the config file's list rather than replacing it.
+pub fn merge_skip_filenames(cli: &[String], toml: Option<&FilesSection>) -> Vec<String> {
+    let mut names = toml
+        .and_then(|t| t.skip_filenames.clone())
+        .unwrap_or_default();
+    names.extend(cli.iter().cloned());
+    names
+}
+
+/// Resolve how many leading bytes to scan when detecting binary files.
+///
+/// Priority: CLI > TOML > default. A full scan (from either source) wins over a byte count
+/// and is returned as `usize::MAX`.
+pub fn merge_binary_check_bytes(
+    cli_bytes: Option<usize>,
+    cli_full: bool,
+    toml: Option<&FilesSection>,
+) -> usize {
+    let full = cli_full || toml.and_then(|t| t.binary_check_full).unwrap_or(false);
+    if full {
+        return usize::MAX;
     }
+    cli_bytes
+        .or_else(|| toml.and_then(|t| t.binary_check_bytes))
+        .unwrap_or(DEFAULT_BINARY_CHECK_BYTES)
 }
 
 #[cfg(test)]
@@ -168,4 +417,107 @@ mod tests {
         assert!(config.remove_leading_blanks); // keep=false -> remove=true
         assert!(config.fix_code_blocks);
     }
+
+    #[test]
+    fn test_merge_toml_disables_secret_detection() {
+        let cli = CliNormalizeOptions::default();
+        let toml: super::super::toml_schema::FiniToml =
+            toml::from_str("[normalize]\ndetect_secrets = false\n").unwrap();
+
+        let config = merge_normalize_config(&cli, Some(&toml.normalize));
+
+        assert!(!config.detect_secrets);
+        assert!(config.detect_todos); // default
+
+        let result = crate::normalize_content("API_KEY = \"sk_live_abcd12345678\"\n", &config);
+        assert!(!result
+            .problems
+            .iter()
+            .any(|p| matches!(p.kind, crate::ProblemKind::SecretPattern { .. })));
+    }
+
+    #[test]
+    fn test_merge_completes_older_rule_order() {
+        let cli = CliNormalizeOptions::default();
+        let toml: super::super::toml_schema::FiniToml = toml::from_str(
+            "[normalize]\nrule_order = [\"line-endings\", \"leading-blank-lines\", \"zero-width\", \
+             \"code-blocks\", \"blank-lines\", \"fullwidth-space\", \"smart-punctuation\", \
+             \"trailing-whitespace\", \"eof-newline\"]\n",
+        )
+        .unwrap();
+
+        let config = merge_normalize_config(&cli, Some(&toml.normalize));
+
+        assert_eq!(config.rule_order.len(), crate::DEFAULT_RULE_ORDER.len());
+        assert_eq!(config.rule_order[1], Rule::LeadingBlankLines);
+        assert_eq!(config.rule_order[3], Rule::ControlWhitespace);
+        assert_eq!(config.rule_order[8], Rule::ListMarkerSpacing);
+    }
+
+    #[test]
+    fn test_merge_skip_filenames_combines_cli_and_toml() {
+        let toml = FilesSection {
+            skip_filenames: Some(vec!["CHANGELOG.md".to_string()]),
+            ..Default::default()
+        };
+
+        let names = merge_skip_filenames(&["LICENSE".to_string()], Some(&toml));
+
+        assert_eq!(names, ["CHANGELOG.md", "LICENSE"]);
+        assert!(merge_skip_filenames(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_merge_binary_check_bytes() {
+        let toml = FilesSection {
+            binary_check_bytes: Some(100),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            merge_binary_check_bytes(None, false, None),
+            DEFAULT_BINARY_CHECK_BYTES
+        );
+        assert_eq!(merge_binary_check_bytes(None, false, Some(&toml)), 100);
+        assert_eq!(merge_binary_check_bytes(Some(50), false, Some(&toml)), 50);
+        assert_eq!(merge_binary_check_bytes(Some(50), true, None), usize::MAX);
+
+        let full = FilesSection {
+            binary_check_full: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            merge_binary_check_bytes(Some(50), false, Some(&full)),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_profile_applies_only_when_selected() {
+        let toml: FiniToml = toml::from_str(
+            "[normalize]\nmax_blank_lines = 2\n\n[profiles.strict]\nmax_line_length = 80\n",
+        )
+        .unwrap();
+        let cli = CliNormalizeOptions::default();
+
+        let config = merge_normalize_config(&cli, Some(&resolve_profile(&toml, None).unwrap()));
+        assert_eq!(config.max_line_length, None);
+        assert_eq!(config.max_blank_lines, Some(2));
+
+        let strict = resolve_profile(&toml, Some("strict")).unwrap();
+        let config = merge_normalize_config(&cli, Some(&strict));
+        assert_eq!(config.max_line_length, Some(80));
+        // Keys the profile doesn't set come from [normalize]
+        assert_eq!(config.max_blank_lines, Some(2));
+    }
+
+    #[test]
+    fn test_unknown_profile_is_an_error() {
+        let toml: FiniToml = toml::from_str("[profiles.lenient]\ndetect_todos = false\n").unwrap();
+        let err = resolve_profile(&toml, Some("strict")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid config file: unknown profile `strict` (defined: lenient)"
+        );
+    }
 }
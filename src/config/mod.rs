@@ -5,16 +5,31 @@
 //! - Config file discovery (search upward from current directory)
 //! - Merging CLI args, config file, and defaults
 //! - Template generation with `--init`
+//! - JSON Schema generation with `--config-schema`
 //! - `.editorconfig` reading for migration assistance
+//! - Per-rule file scopes from `[rules.<rule-id>]`
 
 mod editorconfig;
 mod file;
 mod init;
 mod merge;
+mod options;
+mod schema;
+mod scope;
 mod toml_schema;
 
-pub use editorconfig::{check_editorconfig_conflicts, find_editorconfig, parse_editorconfig};
-pub use file::{find_config_file, find_file_upward, load_config, ConfigError};
-pub use init::{generate_init_file, FINI_TOML_TEMPLATE};
-pub use merge::{merge_normalize_config, CliNormalizeOptions};
-pub use toml_schema::{FiniToml, NormalizeSection};
+pub use editorconfig::{
+    apply_editorconfig_defaults, check_editorconfig_conflicts, find_editorconfig,
+    parse_editorconfig, EditorConfigSettings,
+};
+pub use file::{
+    find_config_file, find_file_upward, load_config, load_config_with_unknown_keys, ConfigError,
+};
+pub use init::{full_init_template, generate_init_file, FINI_TOML_TEMPLATE};
+pub use merge::{
+    merge_binary_check_bytes, merge_normalize_config, merge_skip_filenames, resolve_profile,
+    CliNormalizeOptions,
+};
+pub use schema::config_schema;
+pub use scope::{build_rule_scopes, scoped_config, RuleScope};
+pub use toml_schema::{FilesSection, FiniToml, NormalizeSection, RuleScopeSection};
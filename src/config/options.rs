@@ -0,0 +1,413 @@
+//! Metadata describing every fini.toml option
+
+use crate::normalize::RULE_IDS;
+
+/// TOML value type of an option
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ValueType {
+    Boolean,
+    Integer,
+    /// String restricted to the listed values
+    Enum(&'static [&'static str]),
+    String,
+    /// Array of strings
+    StringArray,
+    /// Array of strings restricted to the listed values
+    EnumArray(&'static [&'static str]),
+}
+
+/// Documentation for a single fini.toml option
+pub(crate) struct OptionDoc {
+    pub key: &'static str,
+    /// Example value written in the commented-out assignment
+    pub example: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+    pub value_type: ValueType,
+}
+
+/// Documentation for a fini.toml table and its options
+pub(crate) struct SectionDoc {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub options: &'static [OptionDoc],
+}
+
+/// Every fini.toml table, in template order
+pub(crate) const SECTIONS: &[SectionDoc] = &[
+    SectionDoc {
+        name: "normalize",
+        description: "Normalization settings",
+        options: NORMALIZE_OPTIONS,
+    },
+    SectionDoc {
+        name: "files",
+        description: "File selection settings",
+        options: FILES_OPTIONS,
+    },
+];
+
+/// Metadata for every `NormalizeSection` field, used to build the full template and JSON schema
+pub(crate) const NORMALIZE_OPTIONS: &[OptionDoc] = &[
+    OptionDoc {
+        key: "max_blank_lines",
+        example: "2",
+        default: "no limit",
+        description: "Maximum consecutive blank lines (0 = remove all blank lines)",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "remove_zero_width",
+        example: "true",
+        default: "true",
+        description: "Remove zero-width characters (ZWSP, ZWJ, ZWNJ, etc.)",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "remove_leading_blanks",
+        example: "true",
+        default: "true",
+        description: "Remove blank lines at the start of files",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "fix_code_blocks",
+        example: "false",
+        default: "false",
+        description: "Remove markdown code block markers (``` fences)",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "detect_todos",
+        example: "true",
+        default: "true",
+        description: "Report TODO comments",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "detect_fixmes",
+        example: "true",
+        default: "true",
+        description: "Report FIXME comments",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "detect_debug",
+        example: "true",
+        default: "true",
+        description: "Report debug code like console.log, print(), dbg!()",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "strict_debug",
+        example: "false",
+        default: "false",
+        description: "Include console.error/eprintln in debug code detection",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "detect_secrets",
+        example: "true",
+        default: "true",
+        description: "Report potential secrets like API keys and tokens",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "max_line_length",
+        example: "120",
+        default: "disabled",
+        description: "Report lines longer than N characters",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "indent_size",
+        example: "2",
+        default: "disabled",
+        description: "Report lines whose leading spaces are not a multiple of N",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "line_length_mode",
+        example: "\"display-width\"",
+        default: "char-count",
+        description: "How max_line_length is measured: char-count or display-width (wide characters count as 2)",
+        value_type: ValueType::Enum(&["char-count", "display-width"]),
+    },
+    OptionDoc {
+        key: "fix_fullwidth_space",
+        example: "true",
+        default: "true",
+        description: "Replace full-width spaces (U+3000) with ASCII spaces; false only reports them",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "rule_order",
+        example: "[\"line-endings\", \"zero-width\", \"control-whitespace\", \"code-blocks\", \"leading-blank-lines\", \"blank-lines\", \"fullwidth-space\", \"smart-punctuation\", \"list-marker-spacing\", \"trailing-whitespace\", \"eof-newline\"]",
+        default: "[\"line-endings\", \"zero-width\", \"control-whitespace\", \"code-blocks\", \"leading-blank-lines\", \"blank-lines\", \"fullwidth-space\", \"smart-punctuation\", \"list-marker-spacing\", \"trailing-whitespace\", \"eof-newline\"]",
+        description: "Order of the fixing rules; each rule at most once, line-endings first and eof-newline last (rules left out run in their default position)",
+        value_type: ValueType::EnumArray(RULE_IDS),
+    },
+    OptionDoc {
+        key: "markdown_preserve_code",
+        example: "false",
+        default: "false",
+        description: "Keep trailing whitespace on lines inside ``` fenced code blocks",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "require_shebang_exts",
+        example: "[\"sh\", \"bash\"]",
+        default: "[]",
+        description: "Report files with these extensions (without the dot) that don't start with #!",
+        value_type: ValueType::StringArray,
+    },
+    OptionDoc {
+        key: "preserve_string_trailing_ws",
+        example: "false",
+        default: "false",
+        description: "Keep trailing whitespace inside multi-line string literals (\"\"\", ''', r\"...\"); best-effort",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "normalize_smart_quotes",
+        example: "false",
+        default: "false",
+        description: "Replace curly quotes (“ ” ‘ ’) with straight ASCII quotes",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "normalize_dashes",
+        example: "false",
+        default: "false",
+        description: "With normalize_smart_quotes, also replace em dashes (—) with -- and en dashes (–) with -",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "max_file_lines",
+        example: "1000",
+        default: "disabled",
+        description: "Report files with more than N lines (once per file)",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "detect_base64_blobs",
+        example: "false",
+        default: "false",
+        description: "Report long base64 strings assigned to variables (embedded credentials or binaries)",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "base64_min_length",
+        example: "200",
+        default: "200",
+        description: "Shortest base64 run reported by detect_base64_blobs",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "base64_skip_data_uris",
+        example: "true",
+        default: "true",
+        description: "Skip data:image/...;base64, URIs when detecting base64 blobs",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "normalize_form_feeds",
+        example: "true",
+        default: "false",
+        description: "Remove form feed (\\x0C) and vertical tab (\\x0B) characters, as set by form_feed_replacement",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "form_feed_replacement",
+        example: "\"newline\"",
+        default: "remove",
+        description: "With normalize_form_feeds, remove the characters (remove) or turn each into a line break (newline)",
+        value_type: ValueType::Enum(&["remove", "newline"]),
+    },
+    OptionDoc {
+        key: "markdown_list_spacing",
+        example: "true",
+        default: "false",
+        description: "In .md files, replace the whitespace after -, *, + and 1. list markers with a single space",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "secret_allowlist",
+        example: "[\"my-public-demo-token\"]",
+        default: "[]",
+        description: "Lines containing any of these values are never reported as secrets",
+        value_type: ValueType::StringArray,
+    },
+    OptionDoc {
+        key: "builtin_secret_allowlist",
+        example: "false",
+        default: "true",
+        description: "Skip well-known example secrets (AWS documentation keys, Stripe sk_test_ keys)",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "required_header",
+        example: "\"// Copyright (c) Example Corp\\n// SPDX-License-Identifier: MIT\"",
+        default: "disabled",
+        description: "Report files that do not start with this text (after a #! line); compared ignoring trailing whitespace and line endings",
+        value_type: ValueType::String,
+    },
+    OptionDoc {
+        key: "fix_header",
+        example: "false",
+        default: "false",
+        description: "With required_header, prepend the header to files that are missing it",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "trim_trailing_blank_lines",
+        example: "true",
+        default: "true",
+        description: "Remove blank lines before the end of the file",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "promote_shebang",
+        example: "false",
+        default: "false",
+        description: "Move a #! line found in the first few lines to line 1",
+        value_type: ValueType::Boolean,
+    },
+    OptionDoc {
+        key: "require_todo_reference",
+        example: "false",
+        default: "false",
+        description: "Only report TODO/FIXME comments without a (ref) or [ref] after the marker",
+        value_type: ValueType::Boolean,
+    },
+];
+
+/// Metadata for every `FilesSection` field
+pub(crate) const FILES_OPTIONS: &[OptionDoc] = &[
+    OptionDoc {
+        key: "skip_filenames",
+        example: "[\"CHANGELOG.md\", \"LICENSE\"]",
+        default: "[]",
+        description: "File names (not paths) to always leave untouched",
+        value_type: ValueType::StringArray,
+    },
+    OptionDoc {
+        key: "binary_check_bytes",
+        example: "65536",
+        default: "8192",
+        description: "Number of leading bytes scanned for null bytes to detect binary files",
+        value_type: ValueType::Integer,
+    },
+    OptionDoc {
+        key: "binary_check_full",
+        example: "false",
+        default: "false",
+        description: "Scan entire files for null bytes (overrides binary_check_bytes)",
+        value_type: ValueType::Boolean,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FilesSection, NormalizeSection};
+
+    fn uncommented(options: &[OptionDoc]) -> String {
+        options
+            .iter()
+            .map(|o| format!("{} = {}\n", o.key, o.example))
+            .collect()
+    }
+
+    #[test]
+    fn test_options_cover_every_field() {
+        // Assign every documented option and make sure all fields get set.
+        let section: NormalizeSection = toml::from_str(&uncommented(NORMALIZE_OPTIONS)).unwrap();
+
+        // Exhaustive destructuring: adding a field without documenting it fails to compile
+        let NormalizeSection {
+            max_blank_lines,
+            remove_zero_width,
+            remove_leading_blanks,
+            fix_code_blocks,
+            detect_todos,
+            detect_fixmes,
+            detect_debug,
+            strict_debug,
+            detect_secrets,
+            max_line_length,
+            indent_size,
+            line_length_mode,
+            fix_fullwidth_space,
+            rule_order,
+            markdown_preserve_code,
+            require_shebang_exts,
+            preserve_string_trailing_ws,
+            normalize_smart_quotes,
+            normalize_dashes,
+            max_file_lines,
+            detect_base64_blobs,
+            base64_min_length,
+            base64_skip_data_uris,
+            normalize_form_feeds,
+            form_feed_replacement,
+            markdown_list_spacing,
+            secret_allowlist,
+            builtin_secret_allowlist,
+            required_header,
+            fix_header,
+            trim_trailing_blank_lines,
+            promote_shebang,
+            require_todo_reference,
+        } = section;
+
+        assert!(max_blank_lines.is_some());
+        assert!(remove_zero_width.is_some());
+        assert!(remove_leading_blanks.is_some());
+        assert!(fix_code_blocks.is_some());
+        assert!(detect_todos.is_some());
+        assert!(detect_fixmes.is_some());
+        assert!(detect_debug.is_some());
+        assert!(strict_debug.is_some());
+        assert!(detect_secrets.is_some());
+        assert!(max_line_length.is_some());
+        assert!(indent_size.is_some());
+        assert!(line_length_mode.is_some());
+        assert!(fix_fullwidth_space.is_some());
+        assert!(rule_order.is_some());
+        assert!(markdown_preserve_code.is_some());
+        assert!(require_shebang_exts.is_some());
+        assert!(preserve_string_trailing_ws.is_some());
+        assert!(normalize_smart_quotes.is_some());
+        assert!(normalize_dashes.is_some());
+        assert!(max_file_lines.is_some());
+        assert!(detect_base64_blobs.is_some());
+        assert!(base64_min_length.is_some());
+        assert!(base64_skip_data_uris.is_some());
+        assert!(normalize_form_feeds.is_some());
+        assert!(form_feed_replacement.is_some());
+        assert!(markdown_list_spacing.is_some());
+        assert!(secret_allowlist.is_some());
+        assert!(builtin_secret_allowlist.is_some());
+        assert!(required_header.is_some());
+        assert!(fix_header.is_some());
+        assert!(trim_trailing_blank_lines.is_some());
+        assert!(promote_shebang.is_some());
+        assert!(require_todo_reference.is_some());
+    }
+
+    #[test]
+    fn test_files_options_cover_every_field() {
+        let section: FilesSection = toml::from_str(&uncommented(FILES_OPTIONS)).unwrap();
+
+        let FilesSection {
+            skip_filenames,
+            binary_check_bytes,
+            binary_check_full,
+        } = section;
+
+        assert!(skip_filenames.is_some());
+        assert!(binary_check_bytes.is_some());
+        assert!(binary_check_full.is_some());
+    }
+}
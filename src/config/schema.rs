@@ -0,0 +1,191 @@
+//! JSON Schema for fini.toml, printed by `--config-schema`
+
+use serde_json::{json, Map, Value};
+
+use super::options::{OptionDoc, ValueType, SECTIONS};
+use crate::normalize::{DETECTION_RULE_IDS, RULE_IDS};
+
+/// Build a JSON Schema (draft-07) describing fini.toml.
+pub fn config_schema() -> Value {
+    let mut sections: Map<String, Value> = SECTIONS
+        .iter()
+        .map(|section| {
+            let mut properties = option_properties(section.options);
+            if section.name == "normalize" {
+                add_kebab_case_aliases(&mut properties);
+            }
+            let schema = json!({
+                "description": section.description,
+                "type": "object",
+                "properties": properties,
+                "additionalProperties": false,
+            });
+            (section.name.to_string(), schema)
+        })
+        .collect();
+    sections.insert("rules".to_string(), rules_schema());
+    let profile = sections["normalize"].clone();
+    sections.insert(
+        "profiles".to_string(),
+        json!({
+            "description": "Named [normalize] overrides selected with --profile",
+            "type": "object",
+            "additionalProperties": profile,
+        }),
+    );
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "fini.toml",
+        "description": "Configuration for the fini file normalizer",
+        "type": "object",
+        "properties": sections,
+    })
+}
+
+/// `[rules.<rule-id>]` tables with include/exclude glob lists
+fn rules_schema() -> Value {
+    let globs = json!({ "type": "array", "items": { "type": "string" } });
+    let rule_ids: Vec<&str> = DETECTION_RULE_IDS.iter().chain(RULE_IDS).copied().collect();
+    json!({
+        "description": "Limit detection and fixing rules to files matching globs",
+        "type": "object",
+        "propertyNames": { "enum": rule_ids },
+        "additionalProperties": {
+            "type": "object",
+            "properties": { "include": globs, "exclude": globs },
+            "additionalProperties": false,
+        },
+    })
+}
+
+/// Add the kebab-case spelling `[normalize]` also accepts for each snake_case key
+fn add_kebab_case_aliases(properties: &mut Map<String, Value>) {
+    let aliases: Vec<(String, Value)> = properties
+        .iter()
+        .filter(|(key, _)| key.contains('_'))
+        .map(|(key, property)| {
+            let mut alias = property.clone();
+            alias["description"] = json!(format!("Alias of `{key}`"));
+            (key.replace('_', "-"), alias)
+        })
+        .collect();
+    properties.extend(aliases);
+}
+
+fn option_properties(options: &[OptionDoc]) -> Map<String, Value> {
+    let mut properties = Map::new();
+
+    for option in options {
+        let mut property = match option.value_type {
+            ValueType::Boolean => json!({ "type": "boolean" }),
+            ValueType::Integer => json!({ "type": "integer", "minimum": 0 }),
+            ValueType::String => json!({ "type": "string" }),
+            ValueType::Enum(values) => json!({ "type": "string", "enum": values }),
+            ValueType::StringArray => json!({ "type": "array", "items": { "type": "string" } }),
+            ValueType::EnumArray(values) => {
+                json!({ "type": "array", "items": { "type": "string", "enum": values } })
+            }
+        };
+        property["description"] = json!(option.description);
+
+        // Only options with a concrete default (not "disabled"/"no limit") get one
+        let default = match option.value_type {
+            ValueType::Boolean => option.default.parse::<bool>().ok().map(Value::from),
+            ValueType::Integer => option.default.parse::<u64>().ok().map(Value::from),
+            // String options have no default; they are disabled when unset
+            ValueType::String => None,
+            ValueType::Enum(values) => values
+                .contains(&option.default)
+                .then(|| json!(option.default)),
+            ValueType::StringArray | ValueType::EnumArray(_) => {
+                serde_json::from_str::<Vec<String>>(option.default)
+                    .ok()
+                    .map(Value::from)
+            }
+        };
+        if let Some(default) = default {
+            property["default"] = default;
+        }
+
+        properties.insert(option.key.to_string(), property);
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_describes_normalize_section() {
+        let schema = config_schema();
+        let normalize = &schema["properties"]["normalize"]["properties"];
+
+        assert_eq!(normalize["max_blank_lines"]["type"], "integer");
+        assert_eq!(normalize["detect_secrets"]["type"], "boolean");
+        assert_eq!(normalize["detect_secrets"]["default"], true);
+        assert!(normalize["max_line_length"].get("default").is_none());
+        assert_eq!(normalize["line_length_mode"]["type"], "string");
+        assert_eq!(normalize["line_length_mode"]["default"], "char-count");
+        assert_eq!(normalize["rule_order"]["items"]["enum"][0], "line-endings");
+        assert_eq!(normalize["rule_order"]["default"][10], "eof-newline");
+    }
+
+    #[test]
+    fn test_schema_lists_every_option() {
+        let schema = config_schema();
+
+        for section in SECTIONS {
+            let properties = schema["properties"][section.name]["properties"]
+                .as_object()
+                .unwrap();
+            let keys = properties.keys().filter(|key| !key.contains('-'));
+            assert_eq!(keys.count(), section.options.len());
+        }
+    }
+
+    #[test]
+    fn test_schema_accepts_kebab_case_normalize_keys() {
+        let schema = config_schema();
+        let normalize = &schema["properties"]["normalize"]["properties"];
+
+        assert_eq!(normalize["max-blank-lines"]["type"], "integer");
+        assert_eq!(
+            normalize["max-blank-lines"]["description"],
+            "Alias of `max_blank_lines`"
+        );
+        assert_eq!(
+            normalize["rule-order"]["items"],
+            normalize["rule_order"]["items"]
+        );
+        // Every snake_case key has its alias
+        let keys = normalize.as_object().unwrap().keys();
+        for key in keys.filter(|key| key.contains('_')) {
+            assert!(normalize.get(key.replace('_', "-")).is_some(), "{key}");
+        }
+        // [files] keys have no aliases
+        assert!(schema["properties"]["files"]["properties"]
+            .get("skip-filenames")
+            .is_none());
+    }
+
+    #[test]
+    fn test_schema_describes_profiles() {
+        let schema = config_schema();
+        let profile = &schema["properties"]["profiles"]["additionalProperties"];
+
+        assert_eq!(profile, &schema["properties"]["normalize"]);
+    }
+
+    #[test]
+    fn test_schema_describes_files_section() {
+        let schema = config_schema();
+        let skip = &schema["properties"]["files"]["properties"]["skip_filenames"];
+
+        assert_eq!(skip["type"], "array");
+        assert_eq!(skip["items"]["type"], "string");
+        assert_eq!(skip["default"], json!([]));
+    }
+}
@@ -0,0 +1,139 @@
+//! Per-rule file scopes (`[rules.<rule-id>]` in fini.toml)
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use globset::GlobSet;
+
+use super::toml_schema::RuleScopeSection;
+use crate::normalize::{NormalizeConfig, Rule};
+use crate::walker::build_globset;
+
+/// The files a detection rule runs on, or a fixing rule may change
+#[derive(Debug)]
+pub struct RuleScope {
+    pub rule_id: String,
+    /// `None` means every file not excluded
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl RuleScope {
+    /// Whether the rule runs on `path`. Globs match the whole path (with `/`
+    /// separators) or just the file name, so `*.env` works in any directory.
+    pub fn applies_to(&self, path: &Path) -> bool {
+        let candidate = path.to_string_lossy().replace('\\', "/");
+        let candidate = candidate.strip_prefix("./").unwrap_or(&candidate);
+        let name = path.file_name().map(|n| n.to_string_lossy());
+        let matches = |globs: &GlobSet| {
+            globs.is_match(candidate) || name.as_ref().is_some_and(|n| globs.is_match(n.as_ref()))
+        };
+
+        let included = self.include.as_ref().is_none_or(matches);
+        included && !matches(&self.exclude)
+    }
+}
+
+/// Compile the `[rules.*]` sections of fini.toml.
+pub fn build_rule_scopes(
+    sections: &BTreeMap<String, RuleScopeSection>,
+) -> io::Result<Vec<RuleScope>> {
+    sections
+        .iter()
+        .map(|(rule_id, section)| {
+            let include = section.include.as_deref().map(build_globset).transpose()?;
+            let exclude = build_globset(section.exclude.as_deref().unwrap_or_default())?;
+            Ok(RuleScope {
+                rule_id: rule_id.clone(),
+                include,
+                exclude,
+            })
+        })
+        .collect()
+}
+
+/// The config for `path`, with detections whose scope excludes it turned off
+/// and fixing rules whose scope excludes it masked out of `fix_kinds`.
+pub fn scoped_config<'a>(
+    config: &'a NormalizeConfig,
+    scopes: &[RuleScope],
+    path: &Path,
+) -> Cow<'a, NormalizeConfig> {
+    let mut config = Cow::Borrowed(config);
+    for scope in scopes.iter().filter(|scope| !scope.applies_to(path)) {
+        let config = config.to_mut();
+        if !config.disable_detection(&scope.rule_id) {
+            if let Ok(rule) = scope.rule_id.parse::<Rule>() {
+                config.disable_fix(rule);
+            }
+        }
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(toml: &str) -> Vec<RuleScope> {
+        let sections: BTreeMap<String, RuleScopeSection> = toml::from_str(toml).unwrap();
+        build_rule_scopes(&sections).unwrap()
+    }
+
+    #[test]
+    fn test_exclude_disables_rule_for_matching_path() {
+        let scopes = scopes("[secret-pattern]\nexclude = [\"*.test.js\"]\n");
+        let config = NormalizeConfig::default();
+
+        let excluded = scoped_config(&config, &scopes, Path::new("src/app.test.js"));
+        assert!(!excluded.detect_secrets);
+
+        let included = scoped_config(&config, &scopes, Path::new("src/app.js"));
+        assert!(included.detect_secrets);
+        assert!(matches!(included, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_include_limits_rule_to_matching_paths() {
+        let scopes = scopes("[todo-comment]\ninclude = [\"*.env\", \"config/**\"]\n");
+        let config = NormalizeConfig::default();
+
+        assert!(scoped_config(&config, &scopes, Path::new("./app/.env")).detect_todos);
+        assert!(scoped_config(&config, &scopes, Path::new("config/db.yml")).detect_todos);
+        assert!(!scoped_config(&config, &scopes, Path::new("src/main.rs")).detect_todos);
+        // Other detections are unaffected
+        assert!(scoped_config(&config, &scopes, Path::new("src/main.rs")).detect_secrets);
+    }
+
+    #[test]
+    fn test_exclude_unreferenced_todo_drops_reference_requirement() {
+        let scopes = scopes("[unreferenced-todo]\nexclude = [\"scratch/**\"]\n");
+        let config = NormalizeConfig {
+            require_todo_reference: true,
+            ..NormalizeConfig::default()
+        };
+
+        let scratch = scoped_config(&config, &scopes, Path::new("scratch/notes.rs"));
+        assert!(!scratch.require_todo_reference);
+        assert!(scratch.detect_todos);
+
+        assert!(scoped_config(&config, &scopes, Path::new("src/main.rs")).require_todo_reference);
+    }
+
+    #[test]
+    fn test_exclude_masks_fixing_rule() {
+        let scopes = scopes("[eof-newline]\nexclude = [\"*.csv\"]\n");
+        let config = NormalizeConfig::default();
+
+        let csv = scoped_config(&config, &scopes, Path::new("data/dump.csv"));
+        let fix_kinds = csv.fix_kinds.as_ref().unwrap();
+        assert!(!fix_kinds.contains(&Rule::EofNewline));
+        assert!(fix_kinds.contains(&Rule::TrailingWhitespace));
+
+        assert!(scoped_config(&config, &scopes, Path::new("src/main.rs"))
+            .fix_kinds
+            .is_none());
+    }
+}
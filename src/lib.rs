@@ -1,48 +1,168 @@
+mod atomic;
 pub mod colors;
 pub mod config;
+mod lock;
+mod mode;
 pub mod normalize;
 mod output;
 pub mod progress;
+mod replay;
+mod report;
 pub mod walker;
 
+pub use atomic::write_atomic;
 pub use colors::{should_use_colors, Colors};
 pub use config::{
-    check_editorconfig_conflicts, find_config_file, find_editorconfig, generate_init_file,
-    load_config, merge_normalize_config, parse_editorconfig, CliNormalizeOptions, ConfigError,
-    FiniToml, NormalizeSection, FINI_TOML_TEMPLATE,
+    apply_editorconfig_defaults, build_rule_scopes, check_editorconfig_conflicts, config_schema,
+    find_config_file, find_editorconfig, full_init_template, generate_init_file, load_config,
+    load_config_with_unknown_keys, merge_binary_check_bytes, merge_normalize_config,
+    merge_skip_filenames, parse_editorconfig, resolve_profile, scoped_config, CliNormalizeOptions,
+    ConfigError, EditorConfigSettings, FilesSection, FiniToml, NormalizeSection, RuleScope,
+    RuleScopeSection, FINI_TOML_TEMPLATE,
+};
+pub use lock::{lock_root, RunLock, LOCK_FILE_NAME};
+pub use normalize::{
+    complete_rule_order, normalize_content, normalize_file_content, repeat_until_stable,
+    trace_content, trace_file_content, validate_rule_order, FormFeedReplacement, LineLengthMode,
+    NormalizeConfig, NormalizeResult, Problem, ProblemKind, Rule, Severity, TraceStage,
+    BUILTIN_SECRET_ALLOWLIST, DEFAULT_RULE_ORDER, DETECTION_RULE_IDS, MAX_STABLE_PASSES, RULE_IDS,
+};
+pub use output::{
+    line_ops, print_diff, render_stats_table, render_trace, write_diff, Config, CrlfWriter,
+    FileResult, LineOp, OutputContext, OutputFormat, OutputLineEnding, OutputMode, OutputSink,
+    RunResult, SkipReason,
+};
+pub use progress::{is_ci, should_show_progress, ProgressReporter};
+pub use replay::{apply_report, content_sha256, ReplayOutcome};
+pub use report::{
+    build_grouped, build_json_diff, build_rdjson, build_report, build_summary, build_tsv,
+    problem_counts, write_report,
+};
+pub use walker::{
+    build_globset, explain_walk_skip, read_patterns_file, walk_paths, walk_paths_with, WalkError,
+    WalkOptions, WalkSkip,
 };
-pub use normalize::{normalize_content, NormalizeConfig, NormalizeResult, Problem, ProblemKind};
-pub use output::{print_diff, Config, OutputContext, OutputMode, RunResult};
-pub use progress::ProgressReporter;
-pub use walker::walk_paths;
 
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use output::errln;
+use std::fmt;
 use std::fs;
-use std::io;
+use std::io::{self, Read, Write};
 use std::path::Path;
 
-const BINARY_CHECK_SIZE: usize = 8192;
+/// Default number of leading bytes scanned for null bytes
+pub const DEFAULT_BINARY_CHECK_BYTES: usize = 8192;
 
-/// Check if content is binary by looking for null bytes in first 8192 bytes
-pub fn is_binary(content: &[u8]) -> bool {
-    let check_len = content.len().min(BINARY_CHECK_SIZE);
+/// Default size above which a file without line breaks is skipped (1 MiB)
+pub const DEFAULT_MAX_SINGLE_LINE_BYTES: usize = 1024 * 1024;
+
+/// Check if content is binary by looking for null bytes in the first `check_bytes` bytes.
+///
+/// Pass `usize::MAX` to scan the entire content.
+pub fn is_binary(content: &[u8], check_bytes: usize) -> bool {
+    let check_len = content.len().min(check_bytes);
     content[..check_len].contains(&0)
 }
 
+/// Check if content is a single huge line (e.g. minified JS) that isn't worth scanning.
+///
+/// A `max_bytes` of 0 disables the check.
+pub fn is_single_long_line(content: &[u8], max_bytes: usize) -> bool {
+    max_bytes > 0 && content.len() > max_bytes && !content.iter().any(|&b| b == b'\n' || b == b'\r')
+}
+
+/// Check whether `reader` holds more than `limit` lines, reading in chunks and
+/// stopping as soon as the limit is passed so huge files aren't loaded whole.
+pub fn exceeds_line_count(mut reader: impl Read, limit: usize) -> io::Result<bool> {
+    let mut buf = vec![0; 64 * 1024];
+    let mut lines = 0;
+    let mut last = None;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        lines += buf[..n].iter().filter(|&&b| b == b'\n').count();
+        if lines > limit {
+            return Ok(true);
+        }
+        last = Some(buf[n - 1]);
+    }
+    // A final line without a newline still counts
+    let partial = usize::from(last.is_some_and(|b| b != b'\n'));
+    Ok(lines + partial > limit)
+}
+
+/// Detect a UTF-16 byte order mark, returning the matching encoding
+pub fn detect_utf16_bom(content: &[u8]) -> Option<&'static Encoding> {
+    match Encoding::for_bom(content) {
+        Some((encoding, _)) if encoding == UTF_16LE || encoding == UTF_16BE => Some(encoding),
+        _ => None,
+    }
+}
+
+/// Guess the legacy encoding of content that is not valid UTF-8.
+///
+/// Returns `None` when the detector is not confident, so callers skip the
+/// file rather than transcode it with a wild guess.
+pub fn detect_legacy_encoding(content: &[u8]) -> Option<&'static Encoding> {
+    let mut detector = EncodingDetector::new();
+    detector.feed(content, true);
+    match detector.guess_assess(None, false) {
+        (encoding, true) => Some(encoding),
+        (_, false) => None,
+    }
+}
+
 /// Main entry point: process all files in given paths
 pub fn run(paths: &[String], config: &Config, ctx: &OutputContext) -> io::Result<RunResult> {
     let mut result = RunResult {
         files_fixed: 0,
         files_with_problems: 0,
+        files_with_detections: 0,
         warnings: 0,
+        errors: 0,
+        files_scanned: 0,
+        files_processed: 0,
+        files_skipped: 0,
+        files_non_utf8: 0,
+        files: vec![],
     };
 
     // Count files for progress bar (2-pass approach)
-    let file_count: u64 = walk_paths(paths).filter_map(|r| r.ok()).count() as u64;
+    let file_count: u64 = walk_paths_with(paths, &config.walk)
+        .filter_map(|r| r.ok())
+        .count() as u64;
 
     let progress = ProgressReporter::new(file_count, ctx.show_progress);
 
-    for path in walk_paths(paths) {
-        let path = path?;
+    for path in walk_paths_with(paths, &config.walk) {
+        // One unreadable directory shouldn't stop the rest of the run
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                result.errors += 1;
+                if ctx.mode != OutputMode::Quiet {
+                    match &e.path {
+                        Some(path) => {
+                            errln!(
+                                ctx,
+                                "Error walking {}: {}",
+                                ctx.display_path(path).display(),
+                                e.message
+                            )
+                        }
+                        None => errln!(ctx, "Error walking: {}", e.message),
+                    }
+                }
+                continue;
+            }
+        };
+
+        result.files_scanned += 1;
 
         // Update progress bar message with current file name
         if let Some(name) = path.file_name() {
@@ -50,8 +170,13 @@ pub fn run(paths: &[String], config: &Config, ctx: &OutputContext) -> io::Result
         }
 
         if let Err(e) = process_file(&path, config, &mut result, ctx) {
+            result.errors += 1;
             if ctx.mode != OutputMode::Quiet {
-                eprintln!("Error processing {}: {e}", path.display());
+                errln!(
+                    ctx,
+                    "Error processing {}: {e}",
+                    ctx.display_path(&path).display()
+                );
             }
         }
 
@@ -60,55 +185,365 @@ pub fn run(paths: &[String], config: &Config, ctx: &OutputContext) -> io::Result
 
     progress.finish();
 
+    if result.files_scanned == 0 {
+        output::print_no_files_matched(ctx);
+    }
     output::print_summary(&result, config, ctx);
 
     Ok(result)
 }
 
-fn process_file(
+/// Extensions of files left behind by failed merges and patches
+const PATCH_ARTIFACT_EXTENSIONS: &[&str] = &["orig", "rej"];
+
+/// Whether the file looks like a `.orig`/`.rej` merge or patch leftover
+pub fn is_patch_artifact(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| PATCH_ARTIFACT_EXTENSIONS.contains(&ext))
+}
+
+/// Whether the file's name is listed in `skip_filenames`
+fn is_skipped_filename(path: &Path, config: &Config) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| config.skip_filenames.iter().any(|s| s == name))
+}
+
+fn is_over_line_limit(path: &Path, config: &Config) -> io::Result<bool> {
+    match config.skip_over_lines {
+        Some(limit) => exceeds_line_count(fs::File::open(path)?, limit),
+        None => Ok(false),
+    }
+}
+
+/// Decide from the raw bytes whether a file should be skipped before decoding
+fn skip_reason_for_bytes(bytes: &[u8], config: &Config) -> Option<SkipReason> {
+    if bytes.is_empty() {
+        return Some(SkipReason::Empty);
+    }
+
+    // UTF-16 text contains null bytes, so check its BOM before binary detection
+    let utf16 = detect_utf16_bom(bytes);
+    if utf16.is_some() && !config.convert_utf16 {
+        return Some(SkipReason::Utf16);
+    }
+
+    // Binary files are skipped unless forced; non-UTF-8 content still fails decoding
+    if utf16.is_none() && !config.force && is_binary(bytes, config.binary_check_bytes) {
+        return Some(SkipReason::Binary);
+    }
+
+    // Minified bundles and similar single-line blobs
+    if is_single_long_line(bytes, config.max_single_line_bytes) {
+        return Some(SkipReason::SingleLongLine);
+    }
+
+    None
+}
+
+/// Decoded file content, with the source encoding when it was not UTF-8
+struct Decoded {
+    text: String,
+    transcoded_from: Option<&'static Encoding>,
+    /// Stray Windows-1252 bytes in otherwise-UTF-8 content that were repaired
+    repaired_bytes: usize,
+}
+
+/// Decode UTF-16 (with BOM), otherwise UTF-8. Invalid UTF-8 is first repaired
+/// as stray Windows-1252 bytes when `repair_cp1252` is set, then transcoded
+/// from a detected legacy encoding when `auto_encoding` is set. Returns `None`
+/// for invalid content.
+fn decode_content(bytes: Vec<u8>, auto_encoding: bool, repair_cp1252: bool) -> Option<Decoded> {
+    let encoding = match detect_utf16_bom(&bytes) {
+        Some(encoding) => encoding,
+        None => match String::from_utf8(bytes) {
+            Ok(text) => {
+                return Some(Decoded {
+                    text,
+                    transcoded_from: None,
+                    repaired_bytes: 0,
+                })
+            }
+            Err(e) => {
+                if repair_cp1252 {
+                    if let Some((text, repaired_bytes)) = repair_cp1252_bytes(e.as_bytes()) {
+                        return Some(Decoded {
+                            text,
+                            transcoded_from: None,
+                            repaired_bytes,
+                        });
+                    }
+                }
+                if !auto_encoding {
+                    return None;
+                }
+                let bytes = e.into_bytes();
+                let encoding = detect_legacy_encoding(&bytes)?;
+                return decode_with(encoding, &bytes);
+            }
+        },
+    };
+    decode_with(encoding, &bytes)
+}
+
+fn decode_with(encoding: &'static Encoding, bytes: &[u8]) -> Option<Decoded> {
+    let (text, had_errors) = encoding.decode_with_bom_removal(bytes);
+    (!had_errors).then(|| Decoded {
+        text: text.into_owned(),
+        transcoded_from: Some(encoding),
+        repaired_bytes: 0,
+    })
+}
+
+/// Stray Windows-1252 bytes are only repaired when there is at most one per
+/// this many bytes of input
+const CP1252_REPAIR_MIN_SPACING: usize = 8;
+
+/// Decode UTF-8 content with stray Windows-1252 bytes (such as 0x92 for `'`),
+/// converting each isolated invalid byte to its Windows-1252 character.
+/// Returns the text and the number of bytes repaired, or `None` if two
+/// invalid bytes are adjacent or too dense to be stray (as in a legacy
+/// multi-byte encoding) or a byte has no Windows-1252 character.
+fn repair_cp1252_bytes(bytes: &[u8]) -> Option<(String, usize)> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut repaired = 0;
+    let mut after_invalid = false;
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        let invalid = chunk.invalid();
+        if invalid.len() > 1 || (after_invalid && chunk.valid().is_empty() && !invalid.is_empty()) {
+            return None;
+        }
+        after_invalid = !invalid.is_empty();
+        for &byte in invalid {
+            // 0x81, 0x8D, 0x8F, 0x90 and 0x9D are unassigned in Windows-1252
+            if matches!(byte, 0x81 | 0x8D | 0x8F | 0x90 | 0x9D) {
+                return None;
+            }
+            let byte = [byte];
+            let (decoded, _) = WINDOWS_1252.decode_without_bom_handling(&byte);
+            text.push_str(&decoded);
+            repaired += 1;
+        }
+    }
+    if repaired * CP1252_REPAIR_MIN_SPACING > bytes.len() {
+        return None;
+    }
+    Some((text, repaired))
+}
+
+/// Why a file would be skipped
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipExplanation {
+    /// Never yielded by the directory walk
+    Walk(WalkSkip),
+    /// Yielded, but skipped based on its name or content
+    File(SkipReason),
+}
+
+impl fmt::Display for SkipExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipExplanation::Walk(skip) => write!(f, "{skip}"),
+            SkipExplanation::File(reason) => write!(f, "{reason} file"),
+        }
+    }
+}
+
+/// Explain why a run over `root` would skip `path`, or `None` if it would be processed.
+pub fn explain_skip(
+    root: &Path,
     path: &Path,
     config: &Config,
+) -> io::Result<Option<SkipExplanation>> {
+    if let Some(skip) = walker::explain_walk_skip(root, path, &config.walk) {
+        return Ok(Some(SkipExplanation::Walk(skip)));
+    }
+    if is_skipped_filename(path, config) {
+        return Ok(Some(SkipExplanation::File(SkipReason::Configured)));
+    }
+    if is_over_line_limit(path, config)? {
+        return Ok(Some(SkipExplanation::File(SkipReason::TooManyLines)));
+    }
+
+    let bytes = fs::read(path)?;
+    if let Some(reason) = skip_reason_for_bytes(&bytes, config) {
+        return Ok(Some(SkipExplanation::File(reason)));
+    }
+    if decode_content(bytes, config.auto_encoding, config.repair_cp1252).is_none() {
+        return Ok(Some(SkipExplanation::File(SkipReason::NonUtf8)));
+    }
+    Ok(None)
+}
+
+/// Count a file as skipped, mentioning it in verbose mode.
+fn skip_file(
+    path: &Path,
+    reason: SkipReason,
     result: &mut RunResult,
+    config: &Config,
     ctx: &OutputContext,
-) -> io::Result<()> {
+) {
+    result.files_skipped += 1;
+    if reason == SkipReason::NonUtf8 {
+        result.files_non_utf8 += 1;
+    }
+    if ctx.verbose {
+        output::print_skipped(path, reason, config, ctx);
+    }
+}
+
+/// A file read, decoded and normalized by `normalize_file`
+pub struct NormalizedFile {
+    pub result: NormalizeResult,
+    /// The encoding the file was transcoded from, when it was not UTF-8
+    pub transcoded_from: Option<&'static Encoding>,
+    /// Stray Windows-1252 bytes repaired by `repair_cp1252`
+    pub repaired_bytes: usize,
+    /// False when `repeat_until_stable` gave up before the content settled
+    pub stable: bool,
+}
+
+/// Read and decode the file at `path`, or return the reason a run would skip it.
+fn read_file(path: &Path, config: &Config) -> io::Result<Result<Decoded, SkipReason>> {
+    // Skip files listed by name in the configuration
+    if is_skipped_filename(path, config) {
+        return Ok(Err(SkipReason::Configured));
+    }
+
+    // Counted in chunks before reading, so huge files are never loaded
+    if is_over_line_limit(path, config)? {
+        return Ok(Err(SkipReason::TooManyLines));
+    }
+
     let bytes = fs::read(path)?;
 
-    // Skip empty files
-    if bytes.is_empty() {
-        if ctx.verbose {
-            output::print_skipped(path, "empty", ctx);
-        }
-        return Ok(());
+    if let Some(reason) = skip_reason_for_bytes(&bytes, config) {
+        return Ok(Err(reason));
     }
 
-    // Skip binary files
-    if is_binary(&bytes) {
-        if ctx.verbose {
-            output::print_skipped(path, "binary", ctx);
+    Ok(
+        decode_content(bytes, config.auto_encoding, config.repair_cp1252)
+            .ok_or(SkipReason::NonUtf8),
+    )
+}
+
+/// Read, decode and normalize the file at `path` as a run does, without
+/// writing anything. Returns the reason instead when a run would skip it.
+pub fn normalize_file(
+    path: &Path,
+    config: &Config,
+) -> io::Result<Result<NormalizedFile, SkipReason>> {
+    let Decoded {
+        text: content,
+        transcoded_from,
+        repaired_bytes,
+    } = match read_file(path, config)? {
+        Ok(decoded) => decoded,
+        Err(reason) => return Ok(Err(reason)),
+    };
+
+    let normalize = scoped_config(&config.normalize, &config.rule_scopes, path);
+    let (result, stable) = if config.repeat_until_stable {
+        repeat_until_stable(&content, MAX_STABLE_PASSES, |content| {
+            normalize_file_content(path, content, &normalize)
+        })
+    } else {
+        (normalize_file_content(path, &content, &normalize), true)
+    };
+
+    Ok(Ok(NormalizedFile {
+        result,
+        transcoded_from,
+        repaired_bytes,
+        stable,
+    }))
+}
+
+/// Read and decode the file at `path` as `normalize_file` does, recording the
+/// content after each rule of its pipeline (`--trace`)
+pub fn trace_file(
+    path: &Path,
+    config: &Config,
+) -> io::Result<Result<(NormalizeResult, Vec<TraceStage>), SkipReason>> {
+    let decoded = match read_file(path, config)? {
+        Ok(decoded) => decoded,
+        Err(reason) => return Ok(Err(reason)),
+    };
+    let normalize = scoped_config(&config.normalize, &config.rule_scopes, path);
+    Ok(Ok(trace_file_content(path, &decoded.text, &normalize)))
+}
+
+fn process_file(
+    path: &Path,
+    config: &Config,
+    result: &mut RunResult,
+    ctx: &OutputContext,
+) -> io::Result<()> {
+    // Patch artifacts are reported, never modified
+    if config.check_artifacts && is_patch_artifact(path) {
+        result.warnings += 1;
+        if config.check_only {
+            result.files_with_problems += 1;
         }
+        result.files_processed += 1;
+        output::print_artifact(path, config, ctx);
         return Ok(());
     }
 
-    // Try to read as UTF-8
-    let content = match String::from_utf8(bytes) {
-        Ok(s) => s,
-        Err(_) => {
-            if ctx.verbose {
-                output::print_skipped(path, "non-UTF-8", ctx);
-            }
+    let NormalizedFile {
+        result: normalize_result,
+        transcoded_from,
+        repaired_bytes,
+        stable,
+    } = match normalize_file(path, config)? {
+        Ok(file) => file,
+        Err(reason) => {
+            skip_file(path, reason, result, config, ctx);
             return Ok(());
         }
     };
+    let content = &normalize_result.original;
+    result.files_processed += 1;
+    if ctx.verbose {
+        if let Some(encoding) = transcoded_from {
+            output::print_transcoded(path, encoding, ctx);
+        }
+    }
+    if repaired_bytes > 0 {
+        output::print_repaired(path, repaired_bytes, config, ctx);
+    }
+    if !stable {
+        errln!(
+            ctx,
+            "Warning: {} still changes after {MAX_STABLE_PASSES} passes; please report this as a fini bug",
+            ctx.display_path(path).display()
+        );
+    }
 
-    let normalize_result = normalize_content(&content, &config.normalize);
+    // Transcoded and repaired files must be rewritten even if normalization changes nothing
+    let has_changes =
+        normalize_result.has_changes() || transcoded_from.is_some() || repaired_bytes > 0;
 
-    // Check for detection-only problems (these don't change content)
-    let has_detection_problems = normalize_result
+    // Check for problems left to the user (these don't change content)
+    let detections: Vec<&Problem> = normalize_result
         .problems
         .iter()
-        .any(|p| p.kind.is_detection_only());
+        .filter(|p| config.normalize.is_unresolved(&p.kind))
+        .collect();
+    let has_detection_problems = !detections.is_empty();
+
+    #[cfg(unix)]
+    let mode_change = if config.normalize_mode {
+        mode::expected_mode_change(path, &normalize_result.content)?
+    } else {
+        None
+    };
+    #[cfg(not(unix))]
+    let mode_change: Option<mode::ModeChange> = None;
 
-    if !normalize_result.has_changes() && !has_detection_problems {
+    if !has_changes && !has_detection_problems && mode_change.is_none() {
         // No changes and no detection problems
         if ctx.verbose {
             output::print_checked(path, ctx);
@@ -122,28 +557,55 @@ fn process_file(
         .filter(|p| matches!(p.kind, ProblemKind::FullWidthSpace))
         .count();
     result.warnings += fullwidth_count;
+    if has_detection_problems {
+        result.files_with_detections += 1;
+    }
+    // --no-write-on-detection: leave the whole file to the user
+    let held_back = !config.check_only && config.no_write_on_detection && has_detection_problems;
+    result.files.push(FileResult {
+        path: ctx.display_path(path).to_path_buf(),
+        changed: !held_back && (has_changes || mode_change.is_some()),
+        problems: normalize_result.problems.clone(),
+        sha256: config.record_sha256.then(|| content_sha256(content)),
+        line_ops: (config.record_line_ops && has_changes)
+            .then(|| output::line_ops(&normalize_result.original, &normalize_result.content)),
+    });
 
     if config.check_only {
         result.files_with_problems += 1;
         output::print_check_result(path, &normalize_result, config, ctx);
+    } else if held_back {
+        output::print_held_back(path, &detections, ctx);
+        return Ok(());
     } else {
         // Only write if content changed (detection problems don't modify content)
-        if normalize_result.has_changes() {
-            fs::write(path, &normalize_result.content)?;
+        if has_changes {
+            write_atomic(path, normalize_result.content.as_bytes())?;
+        }
+        #[cfg(unix)]
+        if let Some(change) = mode_change {
+            mode::apply_mode_change(path, change)?;
+        }
+        if has_changes || mode_change.is_some() {
             result.files_fixed += 1;
         }
-        // Print fix result if there were changes or detection problems
-        if normalize_result.has_changes() || has_detection_problems {
-            output::print_fix_result(path, &content, &normalize_result, config, ctx);
+        // A mode-only change is reported by its own line below
+        if has_changes {
+            output::print_fix_result(path, content, &normalize_result, config, ctx);
         }
     }
 
+    if let Some(change) = mode_change {
+        output::print_mode_change(path, change, config, ctx);
+    }
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
 
     // ===========================================
     // Phase 1.5: Binary Detection
@@ -152,13 +614,13 @@ mod tests {
     #[test]
     fn test_detect_binary_with_null_byte() {
         let content = b"hello\x00world";
-        assert!(is_binary(content));
+        assert!(is_binary(content, DEFAULT_BINARY_CHECK_BYTES));
     }
 
     #[test]
     fn test_non_binary_text() {
         let content = b"hello world\nthis is text";
-        assert!(!is_binary(content));
+        assert!(!is_binary(content, DEFAULT_BINARY_CHECK_BYTES));
     }
 
     #[test]
@@ -167,7 +629,7 @@ mod tests {
         let mut content = vec![b'a'; 8000];
         content.push(0);
         content.extend(vec![b'b'; 1000]);
-        assert!(is_binary(&content));
+        assert!(is_binary(&content, DEFAULT_BINARY_CHECK_BYTES));
     }
 
     #[test]
@@ -176,12 +638,250 @@ mod tests {
         let mut content = vec![b'a'; 9000];
         content.push(0);
         content.extend(vec![b'b'; 1000]);
-        assert!(!is_binary(&content));
+        assert!(!is_binary(&content, DEFAULT_BINARY_CHECK_BYTES));
+    }
+
+    #[test]
+    fn test_binary_full_scan_detects_late_null() {
+        let mut content = vec![b'a'; 9000];
+        content.push(0);
+        assert!(!is_binary(&content, DEFAULT_BINARY_CHECK_BYTES));
+        assert!(is_binary(&content, 10_000));
+        assert!(is_binary(&content, usize::MAX));
     }
 
     #[test]
     fn test_empty_content_not_binary() {
         let content: &[u8] = b"";
-        assert!(!is_binary(content));
+        assert!(!is_binary(content, DEFAULT_BINARY_CHECK_BYTES));
+    }
+
+    // ===========================================
+    // UTF-16 Detection
+    // ===========================================
+
+    #[test]
+    fn test_detect_utf16_le_bom() {
+        let content = b"\xFF\xFEh\x00i\x00";
+        assert_eq!(detect_utf16_bom(content), Some(UTF_16LE));
+    }
+
+    #[test]
+    fn test_detect_utf16_be_bom() {
+        let content = b"\xFE\xFF\x00h\x00i";
+        assert_eq!(detect_utf16_bom(content), Some(UTF_16BE));
+    }
+
+    const JAPANESE_TEXT: &str = "これは日本語のテキストです。文字コードを自動で判定します。
+";
+
+    fn shift_jis_text() -> Vec<u8> {
+        SHIFT_JIS.encode(JAPANESE_TEXT).0.into_owned()
+    }
+
+    #[test]
+    fn test_detect_shift_jis() {
+        assert_eq!(detect_legacy_encoding(&shift_jis_text()), Some(SHIFT_JIS));
+    }
+
+    #[test]
+    fn test_detect_latin1_as_windows_1252() {
+        let content = b"Le caf\xe9 est tr\xe8s bon, et la cr\xe8me br\xfbl\xe9e aussi.\n";
+        assert_eq!(detect_legacy_encoding(content), Some(WINDOWS_1252));
+    }
+
+    #[test]
+    fn test_decode_content_falls_back_only_with_auto_encoding() {
+        assert!(decode_content(shift_jis_text(), false, false).is_none());
+
+        let decoded = decode_content(shift_jis_text(), true, false).unwrap();
+        assert_eq!(decoded.text, JAPANESE_TEXT);
+        assert_eq!(decoded.transcoded_from, Some(SHIFT_JIS));
+
+        let utf8 = decode_content(b"plain".to_vec(), true, false).unwrap();
+        assert_eq!(utf8.transcoded_from, None);
+    }
+
+    #[test]
+    fn test_decode_content_repairs_stray_cp1252_bytes() {
+        let bytes = b"It\x92s \xe2\x80\x9cfine\xe2\x80\x9d \x96 really\n".to_vec();
+        assert!(decode_content(bytes.clone(), false, false).is_none());
+
+        let decoded = decode_content(bytes, false, true).unwrap();
+        assert_eq!(
+            decoded.text,
+            "It\u{2019}s \u{201c}fine\u{201d} \u{2013} really\n"
+        );
+        assert_eq!(decoded.repaired_bytes, 2);
+        assert_eq!(decoded.transcoded_from, None);
+
+        // Bytes unassigned in Windows-1252 are not guessed at
+        assert!(decode_content(b"a\x81b".to_vec(), false, true).is_none());
+    }
+
+    #[test]
+    fn test_repair_cp1252_leaves_legacy_encodings_to_auto_encoding() {
+        let decoded = decode_content(shift_jis_text(), true, true).unwrap();
+        assert_eq!(decoded.text, JAPANESE_TEXT);
+        assert_eq!(decoded.transcoded_from, Some(SHIFT_JIS));
+        assert_eq!(decoded.repaired_bytes, 0);
+        assert!(decode_content(shift_jis_text(), false, true).is_none());
+
+        // Katakana lead bytes are isolated but far too dense to be stray
+        let katakana = SHIFT_JIS.encode("テキスト\n").0.into_owned();
+        assert!(decode_content(katakana, false, true).is_none());
+    }
+
+    #[test]
+    fn test_utf8_bom_is_not_utf16() {
+        assert_eq!(detect_utf16_bom(b"\xEF\xBB\xBFhi"), None);
+        assert_eq!(detect_utf16_bom(b"hi"), None);
+    }
+
+    // ===========================================
+    // Single Long Line Detection
+    // ===========================================
+
+    #[test]
+    fn test_single_long_line_detected_over_threshold() {
+        let content = vec![b'a'; 2048];
+        assert!(is_single_long_line(&content, 1024));
+    }
+
+    #[test]
+    fn test_single_long_line_not_detected_with_newline() {
+        let mut content = vec![b'a'; 2048];
+        content.push(b'\n');
+        assert!(!is_single_long_line(&content, 1024));
+    }
+
+    #[test]
+    fn test_single_long_line_under_threshold_or_disabled() {
+        let content = vec![b'a'; 2048];
+        assert!(!is_single_long_line(&content, 4096));
+        assert!(!is_single_long_line(&content, 0));
+    }
+
+    #[test]
+    fn test_exceeds_line_count() {
+        assert!(!exceeds_line_count(&b"a\nb\n"[..], 2).unwrap());
+        assert!(exceeds_line_count(&b"a\nb\nc"[..], 2).unwrap());
+        assert!(!exceeds_line_count(&b""[..], 0).unwrap());
+        assert!(exceeds_line_count(&b"a"[..], 0).unwrap());
+    }
+
+    #[test]
+    fn test_exceeds_line_count_stops_early() {
+        // An endless reader only terminates if counting bails out
+        let endless = io::repeat(b'\n');
+        assert!(exceeds_line_count(endless, 1000).unwrap());
+    }
+
+    // ===========================================
+    // Patch Artifact Detection
+    // ===========================================
+
+    #[test]
+    fn test_patch_artifacts_detected_by_extension() {
+        assert!(is_patch_artifact(Path::new("src/main.rs.orig")));
+        assert!(is_patch_artifact(Path::new("foo.rej")));
+        assert!(!is_patch_artifact(Path::new("foo.rs")));
+        assert!(!is_patch_artifact(Path::new("orig")));
+        assert!(!is_patch_artifact(Path::new("foo.original")));
+    }
+
+    // ===========================================
+    // Output Sink
+    // ===========================================
+
+    /// In-memory writer the test keeps a handle to after `run` takes the sink
+    #[derive(Clone, Default)]
+    struct SharedBuf(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn test_config() -> Config {
+        Config {
+            check_only: false,
+            output_mode: OutputMode::Normal,
+            normalize: NormalizeConfig::default(),
+            walk: WalkOptions::default(),
+            convert_utf16: false,
+            auto_encoding: false,
+            repair_cp1252: false,
+            force: false,
+            binary_check_bytes: DEFAULT_BINARY_CHECK_BYTES,
+            skip_filenames: vec![],
+            check_artifacts: false,
+            max_single_line_bytes: DEFAULT_MAX_SINGLE_LINE_BYTES,
+            repeat_until_stable: false,
+            skip_over_lines: None,
+            rule_scopes: vec![],
+            record_line_ops: false,
+            record_sha256: false,
+            no_write_on_detection: false,
+            #[cfg(unix)]
+            normalize_mode: false,
+        }
+    }
+
+    #[test]
+    fn test_run_writes_to_custom_sink() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("test.txt");
+        fs::write(&file, "hello  ").unwrap();
+
+        let config = test_config();
+        let out = SharedBuf::default();
+        let ctx = OutputContext {
+            sink: OutputSink::new(out.clone(), io::sink()),
+            ..OutputContext::new(OutputMode::Normal, false, false, false)
+        };
+
+        let paths = vec![file.display().to_string()];
+        let result = run(&paths, &config, &ctx).unwrap();
+
+        assert_eq!(result.files_fixed, 1);
+        let captured = String::from_utf8(out.0.borrow().clone()).unwrap();
+        assert!(captured.contains(&format!("Fixed: {}", file.display())));
+        assert!(captured.contains("1 files fixed"));
+    }
+
+    // ===========================================
+    // Run Counters
+    // ===========================================
+
+    #[test]
+    fn test_run_counts_processed_and_skipped_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("clean.txt"), "clean\n").unwrap();
+        fs::write(dir.path().join("dirty.txt"), "dirty  ").unwrap();
+        fs::write(dir.path().join("empty.txt"), "").unwrap();
+        fs::write(dir.path().join("image.bin"), b"\x89PNG\0\0").unwrap();
+        fs::write(dir.path().join("latin1.txt"), b"caf\xe9\n").unwrap();
+
+        let out = SharedBuf::default();
+        let ctx = OutputContext {
+            sink: OutputSink::new(out.clone(), io::sink()),
+            ..OutputContext::new(OutputMode::Normal, false, true, false)
+        };
+
+        let paths = vec![dir.path().display().to_string()];
+        let result = run(&paths, &test_config(), &ctx).unwrap();
+
+        assert_eq!(result.files_scanned, 5);
+        assert_eq!(result.files_processed, 2);
+        assert_eq!(result.files_skipped, 3);
+        let captured = String::from_utf8(out.0.borrow().clone()).unwrap();
+        assert!(captured.contains("Processed 2 of 5 files (3 skipped)"));
     }
 }
@@ -0,0 +1,98 @@
+//! Advisory lock preventing concurrent runs on the same tree
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use fs2::FileExt;
+
+/// Name of the lock file created in the root directory
+pub const LOCK_FILE_NAME: &str = ".fini.lock";
+
+/// An exclusive lock on `.fini.lock`, held until dropped.
+///
+/// The lock file itself is left in place; only the lock on it is released.
+#[derive(Debug)]
+pub struct RunLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl RunLock {
+    /// Lock `.fini.lock` in `dir`, failing immediately if another run holds it.
+    pub fn acquire(dir: &Path) -> io::Result<Self> {
+        let path = dir.join(LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        file.try_lock_exclusive().map_err(|e| {
+            if e.kind() == fs2::lock_contended_error().kind() {
+                io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!("another fini run holds {}", path.display()),
+                )
+            } else {
+                e
+            }
+        })?;
+
+        Ok(Self { _file: file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Directory whose `.fini.lock` guards a run over `paths`: the first path
+/// if it is a directory, otherwise its parent (or the current directory).
+pub fn lock_root(paths: &[String]) -> PathBuf {
+    let Some(first) = paths.first().map(Path::new) else {
+        return PathBuf::from(".");
+    };
+
+    if first.is_dir() {
+        return first.to_path_buf();
+    }
+    match first.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_lock_fails_until_released() {
+        let dir = TempDir::new().unwrap();
+
+        let lock = RunLock::acquire(dir.path()).unwrap();
+        assert!(lock.path().exists());
+
+        let err = RunLock::acquire(dir.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        drop(lock);
+        assert!(RunLock::acquire(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_lock_root() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+        let dir_arg = dir.path().to_string_lossy().into_owned();
+        let file_arg = file.to_string_lossy().into_owned();
+
+        assert_eq!(lock_root(&[dir_arg]), dir.path());
+        assert_eq!(lock_root(&[file_arg]), dir.path());
+        assert_eq!(lock_root(&["a.txt".to_string()]), Path::new("."));
+    }
+}
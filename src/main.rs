@@ -1,110 +1,475 @@
+use std::ffi::OsString;
+use std::fs;
 use std::io::{self, IsTerminal, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
 use fini::{
-    check_editorconfig_conflicts, find_config_file, find_editorconfig, generate_init_file,
-    load_config, merge_normalize_config, normalize_content, parse_editorconfig, print_diff, run,
-    should_use_colors, CliNormalizeOptions, Config, FiniToml, OutputContext, OutputMode,
+    apply_editorconfig_defaults, apply_report, build_globset, build_grouped, build_json_diff,
+    build_rdjson, build_report, build_rule_scopes, build_summary, build_tsv,
+    check_editorconfig_conflicts, config_schema, explain_skip, find_config_file, find_editorconfig,
+    generate_init_file, is_binary, is_ci, load_config_with_unknown_keys, lock_root,
+    merge_binary_check_bytes, merge_normalize_config, merge_skip_filenames, normalize_content,
+    normalize_file, parse_editorconfig, problem_counts, read_patterns_file, render_stats_table,
+    render_trace, resolve_profile, run, should_show_progress, should_use_colors, trace_file,
+    write_atomic, write_diff, write_report, CliNormalizeOptions, Colors, Config, CrlfWriter,
+    EditorConfigSettings, FiniToml, LineLengthMode, OutputContext, OutputFormat, OutputLineEnding,
+    OutputMode, OutputSink, Problem, ReplayOutcome, Rule, RunLock, SkipReason, WalkOptions,
+    DEFAULT_MAX_SINGLE_LINE_BYTES,
 };
 
 #[derive(Parser)]
 #[command(name = "fini")]
 #[command(version, about = "A lightweight file normalization CLI tool")]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Target files or directories
-    #[arg(required_unless_present_any = ["init", "stdin"])]
+    #[arg(required_unless_present_any = [
+        "init",
+        "stdin",
+        "config_schema",
+        "why_skipped",
+        "trace",
+        "version_json",
+        "apply_from",
+    ])]
     paths: Vec<String>,
 
     /// Read input from stdin (output to stdout)
     #[arg(long)]
     stdin: bool,
 
+    /// Print the normalized content of a single file to stdout instead of writing it
+    #[arg(long, conflicts_with_all = ["check", "stdin", "diff"])]
+    to_stdout: bool,
+
+    /// With --stdin, write the normalized result to PATH instead of stdout
+    #[arg(long, value_name = "PATH", requires = "stdin")]
+    output: Option<PathBuf>,
+
+    /// Line endings of normalized --stdin output and diffs on stdout: lf (default) or crlf.
+    /// Files are still written with LF.
+    #[arg(long, value_name = "ENDING", default_value = "lf", global = true)]
+    output_line_ending: OutputLineEnding,
+
+    /// With --stdin, also print detected problems as a JSON array to stderr
+    #[arg(long, requires = "stdin")]
+    problems_json: bool,
+
     /// Check only (no modifications), exit 1 if problems found
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     check: bool,
 
+    /// Apply fixes, but exit 1 if detection-only problems (TODOs, secrets, ...) remain
+    #[arg(long, conflicts_with = "check", global = true)]
+    fix_and_check: bool,
+
+    /// Leave files with TODOs, secrets, or other detections unfixed and list them instead
+    #[arg(long, global = true)]
+    no_write_on_detection: bool,
+
+    /// Apply fixes, but exit 1 if any file was modified (for CI)
+    #[arg(long, conflicts_with = "check", global = true)]
+    fail_on_change: bool,
+
+    /// Exit 2 when no files matched the given paths and filters
+    #[arg(long, global = true)]
+    error_on_empty: bool,
+
+    /// Write warnings, errors and the summary to stderr, keeping stdout for diffs and file lists
+    #[arg(long, global = true)]
+    diagnostics_to_stderr: bool,
+
     /// Show changes in diff format
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     diff: bool,
 
+    /// Output format: text, json (with --quiet, a one-line summary), tsv, rdjson (reviewdog),
+    /// or json-diff (per-line edit operations of each changed file)
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "text",
+        conflicts_with = "diff",
+        global = true
+    )]
+    format: OutputFormat,
+
+    /// List the rules that would change each file and the problems found,
+    /// without writing or showing diffs (exits like --check)
+    #[arg(long, conflicts_with_all = ["diff", "format", "json"], global = true)]
+    explain: bool,
+
+    /// With --check, print problems grouped by kind with counts and locations
+    /// instead of per file
+    #[arg(long, conflicts_with_all = ["diff", "format", "json", "explain"], global = true)]
+    group_by_kind: bool,
+
+    /// After the run, print a table of problem counts per kind
+    #[arg(long, global = true)]
+    stats: bool,
+
+    /// Same as --format json
+    #[arg(long, conflicts_with_all = ["diff", "format"], global = true)]
+    json: bool,
+
     /// Output only modified file names
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     quiet: bool,
 
+    /// Print at most N full-width space warnings per file, then "... and X more"
+    #[arg(long, value_name = "N", global = true)]
+    max_warnings_shown: Option<usize>,
+
+    /// Show paths relative to the current directory, even for absolute arguments
+    #[arg(long, global = true)]
+    relative: bool,
+
     /// Show all processed files (including clean ones)
-    #[arg(short = 'v', long)]
+    #[arg(short = 'v', long, global = true)]
     verbose: bool,
 
     /// Force colored output
-    #[arg(long)]
+    #[arg(long, global = true)]
     color: bool,
 
     /// Disable colored output
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_color: bool,
 
     /// Hide progress bar
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_progress: bool,
 
+    /// Show the progress bar even in CI or when stdout isn't a terminal
+    #[arg(long, conflicts_with = "no_progress", global = true)]
+    progress: bool,
+
+    /// Only process files matching the glob (repeatable; default: all files)
+    #[arg(long, value_name = "GLOB", global = true)]
+    include: Vec<String>,
+
+    /// Skip files matching the glob (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude: Vec<String>,
+
+    /// Skip directories whose name matches the glob, without descending (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude_dir: Vec<String>,
+
+    /// Never touch files with this exact name, e.g. CHANGELOG.md (repeatable)
+    #[arg(long, value_name = "NAME", global = true)]
+    skip_file: Vec<String>,
+
+    /// Read exclude globs from a file (one per line, # for comments)
+    #[arg(long, value_name = "FILE", global = true)]
+    exclude_from: Option<PathBuf>,
+
     /// Limit consecutive blank lines to N (0 = remove all blank lines)
-    #[arg(long, value_name = "N")]
+    #[arg(long, value_name = "N", global = true)]
     max_blank_lines: Option<usize>,
 
     /// Keep zero-width characters (default: remove)
-    #[arg(long)]
+    #[arg(long, global = true)]
     keep_zero_width: bool,
 
     /// Keep leading blank lines (default: remove)
-    #[arg(long)]
+    #[arg(long, global = true)]
     keep_leading_blanks: bool,
 
+    /// Keep blank lines at the end of the file (default: remove)
+    #[arg(long, global = true)]
+    keep_trailing_blanks: bool,
+
     /// Remove code block remnants (```lang markers)
-    #[arg(long)]
+    #[arg(long, global = true)]
     fix_code_blocks: bool,
 
     // Phase 3: Human Error Prevention
+    /// Keep trailing whitespace on lines inside ``` fenced code blocks
+    #[arg(long, global = true)]
+    markdown_preserve_code: bool,
+
+    /// In .md files, use exactly one space after list markers (`-  item` -> `- item`)
+    #[arg(long, global = true)]
+    markdown_list_spacing: bool,
+
+    /// Prepend fini.toml's required_header to files that are missing it
+    #[arg(long, global = true)]
+    fix_header: bool,
+
+    /// Move a #! line found in the first few lines of a file to line 1
+    #[arg(long, global = true)]
+    promote_shebang: bool,
+
+    /// Keep trailing whitespace inside """, ''' and r"..." string literals (best-effort)
+    #[arg(long, global = true)]
+    preserve_string_trailing_ws: bool,
+
+    /// Replace curly quotes with straight ASCII quotes
+    #[arg(long, global = true)]
+    fix_smart_quotes: bool,
+
+    /// Remove form feeds and vertical tabs (`form_feed_replacement` in fini.toml can turn them into line breaks)
+    #[arg(long, global = true)]
+    fix_form_feeds: bool,
+
+    /// Report full-width spaces without replacing them
+    #[arg(long, global = true)]
+    no_fix_fullwidth: bool,
+
     /// Skip TODO comment detection
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_detect_todos: bool,
 
+    /// Only report TODO/FIXME comments lacking an issue reference like TODO(#123)
+    #[arg(long, global = true)]
+    require_todo_reference: bool,
+
     /// Skip FIXME comment detection
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_detect_fixmes: bool,
 
     /// Skip debug code detection
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_detect_debug: bool,
 
     /// Include console.error/eprintln in debug code detection
-    #[arg(long)]
+    #[arg(long, global = true)]
     strict_debug: bool,
 
     /// Skip secret pattern detection
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_detect_secrets: bool,
 
+    /// Report long base64 strings assigned to variables
+    #[arg(long, global = true)]
+    detect_base64: bool,
+
     /// Maximum line length (warn if exceeded)
-    #[arg(long, value_name = "N")]
+    #[arg(long, value_name = "N", global = true)]
     max_line_length: Option<usize>,
 
+    /// Maximum number of lines per file (warn if exceeded)
+    #[arg(long, value_name = "N", global = true)]
+    max_file_lines: Option<usize>,
+
+    /// Use .editorconfig's max_line_length when no limit is configured
+    #[arg(long, global = true)]
+    use_editorconfig: bool,
+
+    /// How --max-line-length is measured: char-count (default) or display-width
+    #[arg(long, value_name = "MODE", global = true)]
+    line_length_mode: Option<LineLengthMode>,
+
+    /// Only let these rules modify content, e.g. trailing-whitespace,eof-newline
+    /// (other rules still report problems)
+    #[arg(long, value_name = "RULES", value_delimiter = ',', global = true)]
+    fix_kinds: Option<Vec<Rule>>,
+
+    /// Indentation unit in spaces (warn if a line's indent isn't a multiple)
+    #[arg(long, value_name = "N", global = true)]
+    indent_size: Option<usize>,
+
+    /// Convert UTF-16 files (with BOM) to UTF-8 instead of skipping them
+    #[arg(long, global = true)]
+    convert_utf16: bool,
+
+    /// Detect the encoding of non-UTF-8 files and convert them to UTF-8
+    /// (files with an uncertain guess are skipped)
+    #[arg(long, global = true)]
+    auto_encoding: bool,
+
+    /// Convert stray Windows-1252 bytes (e.g. 0x92 for ') in otherwise-UTF-8 files to UTF-8
+    #[arg(long, global = true)]
+    repair_cp1252: bool,
+
+    /// Scan the first N bytes for null bytes to detect binary files (default: 8192)
+    #[arg(long, value_name = "N", global = true)]
+    binary_check_bytes: Option<usize>,
+
+    /// Scan entire files for null bytes when detecting binary files
+    #[arg(long, global = true)]
+    binary_check_full: bool,
+
+    /// Process files that look binary (they must still be valid UTF-8)
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Report .orig/.rej files left by failed merges or patches (fails --check)
+    #[arg(long, global = true)]
+    check_artifacts: bool,
+
+    /// Set the executable bit on files with a shebang, clear it on others
+    #[cfg(unix)]
+    #[arg(long, global = true)]
+    normalize_mode: bool,
+
+    /// Skip files over N bytes that contain no line breaks (0 = never skip)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_SINGLE_LINE_BYTES, global = true)]
+    max_single_line_bytes: usize,
+
+    /// Re-apply the rules until a file stops changing (at most 5 passes),
+    /// warning about files that never settle
+    #[arg(long, global = true)]
+    repeat_until_stable: bool,
+
+    /// Skip files with more than N lines (counted without loading the file)
+    #[arg(long, value_name = "N", global = true)]
+    skip_over_lines: Option<usize>,
+
+    /// Hold an exclusive lock on .fini.lock for the run; fail if another run holds it
+    #[arg(long, global = true)]
+    lock: bool,
+
+    /// Write a JSON summary of the run to PATH (in addition to console output)
+    #[arg(long, value_name = "PATH", global = true)]
+    report: Option<PathBuf>,
+
     /// Generate a template fini.toml configuration file
     #[arg(long)]
     init: bool,
 
-    /// Specify config file path (overrides auto-discovery)
+    /// With --init, document every available option in the template
+    #[arg(long, requires = "init")]
+    full: bool,
+
+    /// Print the JSON Schema of fini.toml and exit
+    #[arg(long)]
+    config_schema: bool,
+
+    /// Explain whether and why PATH would be skipped, then exit
+    #[arg(long, value_name = "PATH")]
+    why_skipped: Option<PathBuf>,
+
+    /// Print the content and problems after each rule for one file, then exit
     #[arg(long, value_name = "PATH")]
+    trace: Option<PathBuf>,
+
+    /// Print version and build metadata as JSON and exit
+    #[arg(long)]
+    version_json: bool,
+
+    /// Apply the fixes listed in a JSON report (from --report or --json) to files
+    /// that haven't changed since, then exit
+    #[arg(long, value_name = "REPORT", conflicts_with_all = ["check", "stdin"])]
+    apply_from: Option<PathBuf>,
+
+    /// Specify config file path (overrides auto-discovery)
+    #[arg(long, value_name = "PATH", global = true)]
     config: Option<PathBuf>,
+
+    /// Layer fini.toml's [profiles.NAME] over its [normalize] settings
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+}
+
+/// Subcommands; each is equivalent to a flat flag (`fini check` = `fini --check`)
+#[derive(Subcommand)]
+enum Command {
+    /// Fix files in place (the default when only paths are given)
+    Fix {
+        /// Target files or directories
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Check only (no modifications), exit 1 if problems found
+    Check {
+        /// Target files or directories
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Generate a template fini.toml configuration file
+    Init {
+        /// Document every available option in the template
+        #[arg(long)]
+        full: bool,
+    },
+    /// Read input from stdin (output to stdout)
+    Stdin {
+        /// Write the normalized result to PATH instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Also print detected problems as a JSON array to stderr
+        #[arg(long)]
+        problems_json: bool,
+    },
+}
+
+impl Cli {
+    /// Fold a subcommand into the equivalent flat flags.
+    fn apply_command(&mut self) -> Result<(), clap::Error> {
+        match self.command.take() {
+            None => {}
+            Some(Command::Fix { paths }) => self.paths = paths,
+            Some(Command::Check { paths }) => {
+                self.check = true;
+                self.paths = paths;
+            }
+            Some(Command::Init { full }) => {
+                self.init = true;
+                self.full = full;
+            }
+            Some(Command::Stdin {
+                output,
+                problems_json,
+            }) => {
+                self.stdin = true;
+                self.output = output;
+                self.problems_json = problems_json;
+            }
+        }
+
+        // clap only sees these conflicts when --check is passed explicitly
+        if self.check && (self.fix_and_check || self.fail_on_change) {
+            return Err(Cli::command().error(
+                ErrorKind::ArgumentConflict,
+                "`check` cannot be used with '--fix-and-check' or '--fail-on-change'",
+            ));
+        }
+        // `-` reads stdin and writes stdout, like --stdin
+        if self.paths.iter().any(|p| p == "-") {
+            if self.paths.len() > 1 {
+                return Err(Cli::command().error(
+                    ErrorKind::ArgumentConflict,
+                    "'-' (stdin) must be the only path",
+                ));
+            }
+            self.paths.clear();
+            self.stdin = true;
+        }
+        if self.group_by_kind && !self.check {
+            return Err(Cli::command().error(
+                ErrorKind::MissingRequiredArgument,
+                "'--group-by-kind' requires '--check'",
+            ));
+        }
+        Ok(())
+    }
 }
 
 fn main() -> ExitCode {
-    let cli = Cli::parse();
+    let cli = parse_cli();
 
     // Handle --init command
     if cli.init {
-        return handle_init();
+        return handle_init(cli.full);
+    }
+
+    // Handle --version-json command
+    if cli.version_json {
+        return handle_version_json();
+    }
+
+    // Handle --config-schema command
+    if cli.config_schema {
+        return handle_config_schema();
     }
 
     // Handle --stdin command
@@ -116,42 +481,216 @@ fn main() -> ExitCode {
     let toml_config = load_configuration(&cli.config, cli.quiet);
 
     // Check for editorconfig conflicts (informational warnings)
-    if !cli.quiet {
-        check_editorconfig_warnings();
-    }
+    let editorconfig = load_editorconfig(cli.quiet);
 
     // Build CLI options for merging
     let cli_options = build_cli_options(&cli);
 
-    // Merge configurations: CLI > TOML > defaults
-    let normalize =
-        merge_normalize_config(&cli_options, toml_config.as_ref().map(|c| &c.normalize));
+    let section = match (&toml_config, &cli.profile) {
+        (Some(toml), profile) => match resolve_profile(toml, profile.as_deref()) {
+            Ok(section) => Some(section),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            }
+        },
+        (None, Some(name)) => {
+            eprintln!("Error: --profile {name} needs a fini.toml defining [profiles.{name}]");
+            return ExitCode::from(1);
+        }
+        (None, None) => None,
+    };
+
+    // Merge configurations: CLI > TOML (with the selected profile) > defaults
+    let mut normalize = merge_normalize_config(&cli_options, section.as_deref());
+    // .editorconfig only fills in what neither the CLI nor fini.toml set
+    if cli.use_editorconfig {
+        if let Some(settings) = &editorconfig {
+            apply_editorconfig_defaults(settings, &mut normalize);
+        }
+    }
 
-    let output_mode = if cli.quiet {
+    let format = if cli.json {
+        OutputFormat::Json
+    } else {
+        cli.format
+    };
+    let output_mode = if format != OutputFormat::Text || cli.group_by_kind {
+        OutputMode::Structured
+    } else if cli.quiet {
         OutputMode::Quiet
+    } else if cli.explain {
+        OutputMode::Explain
     } else if cli.diff {
         OutputMode::Diff
     } else {
         OutputMode::Normal
     };
 
+    let walk = match build_walk_options(&cli) {
+        Ok(walk) => walk,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+    };
+    let rule_scopes = match toml_config.as_ref().map(|c| build_rule_scopes(&c.rules)) {
+        Some(Ok(scopes)) => scopes,
+        None => vec![],
+        Some(Err(e)) => {
+            eprintln!("Error: rules: {e}");
+            return ExitCode::from(1);
+        }
+    };
+
     let config = Config {
-        check_only: cli.check,
+        // Explaining is a preview, so nothing is written
+        check_only: cli.check || cli.explain,
         output_mode,
         normalize,
+        walk,
+        convert_utf16: cli.convert_utf16,
+        auto_encoding: cli.auto_encoding,
+        repair_cp1252: cli.repair_cp1252,
+        force: cli.force,
+        binary_check_bytes: merge_binary_check_bytes(
+            cli.binary_check_bytes,
+            cli.binary_check_full,
+            toml_config.as_ref().map(|c| &c.files),
+        ),
+        skip_filenames: merge_skip_filenames(
+            &cli.skip_file,
+            toml_config.as_ref().map(|c| &c.files),
+        ),
+        check_artifacts: cli.check_artifacts,
+        max_single_line_bytes: cli.max_single_line_bytes,
+        repeat_until_stable: cli.repeat_until_stable,
+        skip_over_lines: cli.skip_over_lines,
+        rule_scopes,
+        record_line_ops: format == OutputFormat::JsonDiff,
+        // Only reports carry hashes, for `--apply-from`
+        record_sha256: cli.report.is_some() || format == OutputFormat::Json,
+        no_write_on_detection: cli.no_write_on_detection,
+        #[cfg(unix)]
+        normalize_mode: cli.normalize_mode,
     };
 
+    if let Some(report) = &cli.apply_from {
+        return handle_apply_from(report);
+    }
+
+    if let Some(path) = &cli.trace {
+        return handle_trace(path, &config);
+    }
+
+    if cli.to_stdout {
+        return handle_to_stdout(&cli, &config);
+    }
+
+    // Handle --why-skipped diagnostic (relative to the first path, or the current directory)
+    if let Some(path) = &cli.why_skipped {
+        let root = cli.paths.first().map_or(Path::new("."), Path::new);
+        return handle_why_skipped(root, path, &config);
+    }
+
     // Determine color, verbose, and progress settings
     // --quiet overrides --verbose
     let use_colors = should_use_colors(cli.color, cli.no_color);
     let verbose = cli.verbose && !cli.quiet;
-    let show_progress = !cli.quiet && !cli.no_progress && std::io::stdout().is_terminal();
+    let show_progress = should_show_progress(
+        cli.quiet,
+        cli.no_progress,
+        cli.progress,
+        is_ci(),
+        std::io::stdout().is_terminal(),
+    );
 
-    let ctx = OutputContext::new(output_mode, use_colors, verbose, show_progress);
+    let ctx = OutputContext {
+        max_warnings_shown: cli.max_warnings_shown,
+        sink: if output_mode == OutputMode::Diff && cli.output_line_ending == OutputLineEnding::Crlf
+        {
+            OutputSink::new(CrlfWriter::new(io::stdout()), io::stderr())
+        } else {
+            OutputSink::stdio()
+        },
+        relative_to: cli
+            .relative
+            .then(std::env::current_dir)
+            .and_then(Result::ok),
+        diagnostics_to_stderr: cli.diagnostics_to_stderr,
+        ..OutputContext::new(output_mode, use_colors, verbose, show_progress)
+    };
+
+    // Held until the end of main, released on drop
+    let _lock = if cli.lock {
+        match RunLock::acquire(&lock_root(&cli.paths)) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            }
+        }
+    } else {
+        None
+    };
 
     match run(&cli.paths, &config, &ctx) {
         Ok(result) => {
-            if config.check_only && result.has_problems() {
+            if let Some(report_path) = &cli.report {
+                if let Err(e) = write_report(report_path, &result, &config.normalize) {
+                    eprintln!("Error writing report {}: {e}", report_path.display());
+                    return ExitCode::from(1);
+                }
+            }
+
+            match format {
+                OutputFormat::Text if cli.group_by_kind => print!("{}", build_grouped(&result)),
+                OutputFormat::Text => {}
+                // --quiet trades the full report for a one-line summary
+                OutputFormat::Json if cli.quiet => {
+                    println!("{}", build_summary(&result, config.check_only));
+                }
+                OutputFormat::Json => {
+                    match build_report(&result, &config.normalize)
+                        .and_then(|report| serde_json::to_string_pretty(&report))
+                    {
+                        Ok(json) => println!("{json}"),
+                        Err(e) => {
+                            eprintln!("Error: {e}");
+                            return ExitCode::from(1);
+                        }
+                    }
+                }
+                OutputFormat::Tsv => print!("{}", build_tsv(&result)),
+                OutputFormat::JsonDiff | OutputFormat::Rdjson => {
+                    let document = if format == OutputFormat::JsonDiff {
+                        build_json_diff(&result)
+                    } else {
+                        build_rdjson(&result)
+                    };
+                    match serde_json::to_string_pretty(&document) {
+                        Ok(json) => println!("{json}"),
+                        Err(e) => {
+                            eprintln!("Error: {e}");
+                            return ExitCode::from(1);
+                        }
+                    }
+                }
+            }
+            if cli.stats && format == OutputFormat::Text {
+                print!(
+                    "{}",
+                    render_stats_table(&problem_counts(&result), ctx.colors)
+                );
+            }
+
+            if cli.error_on_empty && result.files_scanned == 0 {
+                ExitCode::from(2)
+            } else if result.errors > 0
+                || (config.check_only && result.has_problems())
+                || (cli.fix_and_check && result.has_detections())
+                || (cli.fail_on_change && result.files_fixed > 0)
+            {
                 ExitCode::from(1)
             } else {
                 ExitCode::SUCCESS
@@ -164,8 +703,54 @@ fn main() -> ExitCode {
     }
 }
 
-fn handle_init() -> ExitCode {
-    match generate_init_file() {
+/// Parse arguments, falling back to `FINI_PATHS` / `FINI_CONFIG` for absent ones.
+///
+/// Precedence: explicit CLI arguments > environment variables > config discovery.
+fn parse_cli() -> Cli {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+
+    let mut cli = match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        // No paths given: retry with the ones from FINI_PATHS, if any
+        Err(e) if e.kind() == ErrorKind::MissingRequiredArgument => match env_paths() {
+            Some(paths) => {
+                if !args.iter().any(|a| a == "--") {
+                    args.push("--".into());
+                }
+                args.extend(paths.into_iter().map(OsString::from));
+                Cli::parse_from(args)
+            }
+            None => e.exit(),
+        },
+        Err(e) => e.exit(),
+    };
+
+    if let Err(e) = cli.apply_command() {
+        e.exit();
+    }
+
+    if cli.config.is_none() {
+        cli.config = std::env::var_os("FINI_CONFIG")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+    }
+
+    cli
+}
+
+/// Paths from `FINI_PATHS`, separated by colons or whitespace
+fn env_paths() -> Option<Vec<String>> {
+    let value = std::env::var("FINI_PATHS").ok()?;
+    let paths: Vec<String> = value
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+        .collect();
+    (!paths.is_empty()).then_some(paths)
+}
+
+fn handle_init(full: bool) -> ExitCode {
+    match generate_init_file(full) {
         Ok(path) => {
             println!("Created {}", path.display());
             ExitCode::SUCCESS
@@ -177,14 +762,167 @@ fn handle_init() -> ExitCode {
     }
 }
 
+fn handle_version_json() -> ExitCode {
+    let info = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "commit": option_env!("FINI_GIT_COMMIT"),
+        // No optional cargo features are defined yet
+        "features": Vec::<&str>::new(),
+    });
+    println!("{info}");
+    ExitCode::SUCCESS
+}
+
+fn handle_config_schema() -> ExitCode {
+    match serde_json::to_string_pretty(&config_schema()) {
+        Ok(schema) => {
+            println!("{schema}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn handle_trace(path: &Path, config: &Config) -> ExitCode {
+    match trace_file(path, config) {
+        Ok(Ok((result, stages))) => {
+            print!("{}", render_trace(&stages, &result));
+            ExitCode::SUCCESS
+        }
+        Ok(Err(reason)) => {
+            eprintln!("Error: {} is skipped ({reason} file)", path.display());
+            ExitCode::from(1)
+        }
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", path.display());
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Print the normalized content of the one file in `cli.paths`, leaving it unchanged on disk.
+fn handle_to_stdout(cli: &Cli, config: &Config) -> ExitCode {
+    let [path] = cli.paths.as_slice() else {
+        eprintln!("Error: --to-stdout takes exactly one file path");
+        return ExitCode::from(1);
+    };
+    let path = Path::new(path);
+    if path.is_dir() {
+        eprintln!("Error: --to-stdout takes a file, not a directory");
+        return ExitCode::from(1);
+    }
+
+    // Files a run would skip are printed as they are
+    let content = match normalize_file(path, config) {
+        Ok(Ok(file)) => file.result.content.into_bytes(),
+        Ok(Err(reason)) => {
+            if !cli.quiet {
+                eprintln!(
+                    "Warning: {} is {reason}, printing it unchanged",
+                    path.display()
+                );
+            }
+            match fs::read(path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("Error reading {}: {e}", path.display());
+                    return ExitCode::from(1);
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Error reading {}: {e}", path.display());
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut stdout = stdout_with_line_ending(cli.output_line_ending);
+    if let Err(e) = stdout.write_all(&content).and_then(|()| stdout.flush()) {
+        eprintln!("Error writing stdout: {e}");
+        return ExitCode::from(1);
+    }
+    ExitCode::SUCCESS
+}
+
+fn handle_why_skipped(root: &Path, path: &Path, config: &Config) -> ExitCode {
+    match explain_skip(root, path, config) {
+        Ok(Some(explanation)) => {
+            println!("{}: skipped ({explanation})", path.display());
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            println!("{}: not skipped", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}: {e}", path.display());
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn handle_apply_from(report: &Path) -> ExitCode {
+    let outcomes = match apply_report(report) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            eprintln!("Error reading report {}: {e}", report.display());
+            return ExitCode::from(1);
+        }
+    };
+
+    let mut failed = false;
+    for (path, outcome) in outcomes {
+        let path = path.display();
+        match outcome {
+            Ok(ReplayOutcome::Fixed) => println!("Fixed: {path}"),
+            Ok(ReplayOutcome::Clean) => {}
+            Ok(ReplayOutcome::ChangedSinceReport) => {
+                failed = true;
+                eprintln!("Skipped {path}: changed since the report");
+            }
+            Ok(ReplayOutcome::NoHash) => {
+                failed = true;
+                eprintln!("Skipped {path}: the report has no hash for it");
+            }
+            Ok(ReplayOutcome::NotUtf8) => {
+                failed = true;
+                eprintln!("Skipped {path}: not valid UTF-8");
+            }
+            Err(e) => {
+                failed = true;
+                eprintln!("Error applying {path}: {e}");
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
 fn handle_stdin(cli: &Cli) -> ExitCode {
     // Read from stdin
-    let mut input = String::new();
-    if let Err(e) = io::stdin().read_to_string(&mut input) {
+    let mut bytes = Vec::new();
+    if let Err(e) = io::stdin().read_to_end(&mut bytes) {
         eprintln!("Error reading stdin: {e}");
         return ExitCode::from(1);
     }
 
+    let binary_check_bytes =
+        merge_binary_check_bytes(cli.binary_check_bytes, cli.binary_check_full, None);
+
+    // Binary or non-UTF-8 input passes through unchanged so fini is safe in a pipe
+    let input = match String::from_utf8(bytes) {
+        Ok(text) if !is_binary(text.as_bytes(), binary_check_bytes) => text,
+        Ok(text) => return pass_through_stdin(cli, text.as_bytes(), SkipReason::Binary),
+        Err(e) => return pass_through_stdin(cli, e.as_bytes(), SkipReason::NonUtf8),
+    };
+
     // Build normalize config
     let cli_options = build_cli_options(cli);
     let normalize = merge_normalize_config(&cli_options, None);
@@ -192,6 +930,13 @@ fn handle_stdin(cli: &Cli) -> ExitCode {
     // Normalize content
     let result = normalize_content(&input, &normalize);
 
+    if cli.problems_json {
+        if let Err(e) = print_problems_json(&result.problems) {
+            eprintln!("Error writing problems: {e}");
+            return ExitCode::from(1);
+        }
+    }
+
     // Check for detection-only problems
     let has_detection_problems = result.problems.iter().any(|p| p.kind.is_detection_only());
 
@@ -199,17 +944,54 @@ fn handle_stdin(cli: &Cli) -> ExitCode {
         // Check mode: exit 1 if there are changes or detection problems
         if result.has_changes() || has_detection_problems {
             if cli.diff {
-                // Print diff to stderr so stdout stays clean
-                print_diff("stdin", &input, &result.content);
+                // Check mode writes no content, so stdout only carries the diff
+                let colors = Colors::new(should_use_colors(cli.color, cli.no_color));
+                let mut stdout = stdout_with_line_ending(cli.output_line_ending);
+                // A closed pipe shouldn't abort the run; the diff is informational
+                let _ = write_diff(&mut stdout, "stdin", &input, &result.content, colors);
             }
             return ExitCode::from(1);
         }
         return ExitCode::SUCCESS;
     }
 
-    // Normal mode: output normalized content to stdout
-    print!("{}", result.content);
-    if let Err(e) = io::stdout().flush() {
+    write_stdin_result(cli, result.content.as_bytes(), cli.output_line_ending)
+}
+
+/// Emit stdin input that can't be normalized exactly as it was read.
+fn pass_through_stdin(cli: &Cli, bytes: &[u8], reason: SkipReason) -> ExitCode {
+    if !cli.quiet {
+        eprintln!("Warning: stdin is {reason}, passing it through unchanged");
+    }
+    if cli.problems_json {
+        if let Err(e) = print_problems_json(&[]) {
+            eprintln!("Error writing problems: {e}");
+            return ExitCode::from(1);
+        }
+    }
+
+    // Nothing to fix, so check mode passes
+    if cli.check {
+        return ExitCode::SUCCESS;
+    }
+    write_stdin_result(cli, bytes, OutputLineEnding::Lf)
+}
+
+/// Write the result of `--stdin` to the `--output` file, or to stdout with
+/// `line_ending`.
+fn write_stdin_result(cli: &Cli, bytes: &[u8], line_ending: OutputLineEnding) -> ExitCode {
+    // With --output: write the result to the file atomically
+    if let Some(path) = &cli.output {
+        if let Err(e) = write_atomic(path, bytes) {
+            eprintln!("Error writing {}: {e}", path.display());
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    // Normal mode: output the result to stdout
+    let mut stdout = stdout_with_line_ending(line_ending);
+    if let Err(e) = stdout.write_all(bytes).and_then(|()| stdout.flush()) {
         eprintln!("Error writing stdout: {e}");
         return ExitCode::from(1);
     }
@@ -217,6 +999,20 @@ fn handle_stdin(cli: &Cli) -> ExitCode {
     ExitCode::SUCCESS
 }
 
+/// Stdout, converting `\n` to `\r\n` for `--output-line-ending crlf`
+fn stdout_with_line_ending(line_ending: OutputLineEnding) -> Box<dyn Write> {
+    match line_ending {
+        OutputLineEnding::Lf => Box::new(io::stdout().lock()),
+        OutputLineEnding::Crlf => Box::new(CrlfWriter::new(io::stdout().lock())),
+    }
+}
+
+fn print_problems_json(problems: &[Problem]) -> io::Result<()> {
+    let mut stderr = io::stderr().lock();
+    serde_json::to_writer(&mut stderr, problems)?;
+    writeln!(stderr)
+}
+
 fn load_configuration(explicit_path: &Option<PathBuf>, quiet: bool) -> Option<FiniToml> {
     let config_path = explicit_path.clone().or_else(|| {
         std::env::current_dir()
@@ -224,11 +1020,14 @@ fn load_configuration(explicit_path: &Option<PathBuf>, quiet: bool) -> Option<Fi
             .and_then(|d| find_config_file(&d))
     });
 
-    config_path.and_then(|p| match load_config(&p) {
-        Ok(config) => {
+    config_path.and_then(|p| match load_config_with_unknown_keys(&p) {
+        Ok((config, unknown_keys)) => {
             if !quiet {
                 eprintln!("Using config: {}", p.display());
             }
+            for key in unknown_keys {
+                eprintln!("Warning: unknown config key `{key}` in {}", p.display());
+            }
             Some(config)
         }
         Err(e) => {
@@ -238,17 +1037,33 @@ fn load_configuration(explicit_path: &Option<PathBuf>, quiet: bool) -> Option<Fi
     })
 }
 
-fn check_editorconfig_warnings() {
-    if let Some(editorconfig_path) = std::env::current_dir()
+/// Read the nearest .editorconfig, warning about conflicts unless quiet.
+fn load_editorconfig(quiet: bool) -> Option<EditorConfigSettings> {
+    let editorconfig_path = std::env::current_dir()
         .ok()
-        .and_then(|d| find_editorconfig(&d))
-    {
-        if let Ok(settings) = parse_editorconfig(&editorconfig_path) {
-            for warning in check_editorconfig_conflicts(&settings) {
-                eprintln!("Warning: {}", warning);
-            }
+        .and_then(|d| find_editorconfig(&d))?;
+    let settings = parse_editorconfig(&editorconfig_path).ok()?;
+    if !quiet {
+        for warning in check_editorconfig_conflicts(&settings) {
+            eprintln!("Warning: {}", warning);
         }
     }
+    Some(settings)
+}
+
+fn build_walk_options(cli: &Cli) -> io::Result<WalkOptions> {
+    let mut excludes = cli.exclude.clone();
+    if let Some(path) = &cli.exclude_from {
+        let patterns = read_patterns_file(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        excludes.extend(patterns);
+    }
+
+    Ok(WalkOptions {
+        includes: build_globset(&cli.include)?,
+        excludes: build_globset(&excludes)?,
+        exclude_dirs: build_globset(&cli.exclude_dir)?,
+    })
 }
 
 fn build_cli_options(cli: &Cli) -> CliNormalizeOptions {
@@ -259,13 +1074,28 @@ fn build_cli_options(cli: &Cli) -> CliNormalizeOptions {
         max_blank_lines: cli.max_blank_lines,
         keep_zero_width: cli.keep_zero_width.then_some(true),
         keep_leading_blanks: cli.keep_leading_blanks.then_some(true),
+        keep_trailing_blanks: cli.keep_trailing_blanks.then_some(true),
         fix_code_blocks: cli.fix_code_blocks.then_some(true),
         // Phase 3: Human Error Prevention
+        no_fix_fullwidth: cli.no_fix_fullwidth.then_some(true),
+        markdown_preserve_code: cli.markdown_preserve_code.then_some(true),
+        markdown_list_spacing: cli.markdown_list_spacing.then_some(true),
+        fix_header: cli.fix_header.then_some(true),
+        promote_shebang: cli.promote_shebang.then_some(true),
+        preserve_string_trailing_ws: cli.preserve_string_trailing_ws.then_some(true),
+        fix_smart_quotes: cli.fix_smart_quotes.then_some(true),
+        fix_form_feeds: cli.fix_form_feeds.then_some(true),
         no_detect_todos: cli.no_detect_todos.then_some(true),
+        require_todo_reference: cli.require_todo_reference.then_some(true),
         no_detect_fixmes: cli.no_detect_fixmes.then_some(true),
         no_detect_debug: cli.no_detect_debug.then_some(true),
         strict_debug: cli.strict_debug.then_some(true),
         no_detect_secrets: cli.no_detect_secrets.then_some(true),
+        detect_base64: cli.detect_base64.then_some(true),
         max_line_length: cli.max_line_length,
+        max_file_lines: cli.max_file_lines,
+        line_length_mode: cli.line_length_mode,
+        indent_size: cli.indent_size,
+        fix_kinds: cli.fix_kinds.clone(),
     }
 }
@@ -0,0 +1,62 @@
+//! File permission normalization (executable bit follows the shebang)
+
+/// A pending or applied change to a file's permission bits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeChange {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// Compute the permission bits a file should have.
+///
+/// Executable files get an execute bit for every read bit (0644 -> 0755),
+/// other files lose all execute bits (0755 -> 0644).
+pub fn desired_mode(mode: u32, executable: bool) -> u32 {
+    if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    }
+}
+
+/// Determine whether the file's mode needs to change based on a shebang in `content`.
+#[cfg(unix)]
+pub fn expected_mode_change(
+    path: &std::path::Path,
+    content: &str,
+) -> std::io::Result<Option<ModeChange>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path)?.permissions().mode() & 0o7777;
+    let target = desired_mode(mode, content.starts_with("#!"));
+    Ok((target != mode).then_some(ModeChange {
+        from: mode,
+        to: target,
+    }))
+}
+
+/// Apply a mode change to the file.
+#[cfg(unix)]
+pub fn apply_mode_change(path: &std::path::Path, change: ModeChange) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(change.to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desired_mode_adds_exec_for_read_bits() {
+        assert_eq!(desired_mode(0o644, true), 0o755);
+        assert_eq!(desired_mode(0o600, true), 0o700);
+        assert_eq!(desired_mode(0o755, true), 0o755);
+    }
+
+    #[test]
+    fn test_desired_mode_clears_exec() {
+        assert_eq!(desired_mode(0o755, false), 0o644);
+        assert_eq!(desired_mode(0o644, false), 0o644);
+    }
+}
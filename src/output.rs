@@ -1,19 +1,189 @@
 use crate::colors::Colors;
-use crate::normalize::{NormalizeConfig, NormalizeResult, ProblemKind};
+use crate::config::RuleScope;
+use crate::mode::ModeChange;
+use crate::normalize::{NormalizeConfig, NormalizeResult, Problem, ProblemKind, Rule, TraceStage};
+use crate::walker::WalkOptions;
+use encoding_rs::Encoding;
+use serde::Serialize;
 use similar::{ChangeTag, TextDiff};
-use std::path::Path;
+use std::cell::{RefCell, RefMut};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OutputMode {
     Normal,
+    /// Print only the paths of files that were fixed (or need fixing with
+    /// `--check`), one per line, and nothing else on stdout
     Quiet,
     Diff,
+    /// Print each file that would change with the rules that would fire,
+    /// without writing or diffing
+    Explain,
+    /// Print nothing per file; a JSON or TSV document is written after the run
+    Structured,
+}
+
+/// Format of the run output on stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable per-file output
+    #[default]
+    Text,
+    /// JSON report (a one-line summary with `--quiet`)
+    Json,
+    /// Tab-separated problems, one row per problem
+    Tsv,
+    /// reviewdog Diagnostic Result JSON
+    Rdjson,
+    /// Per-line delete/insert/equal operations for each changed file
+    JsonDiff,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "tsv" => Ok(Self::Tsv),
+            "rdjson" => Ok(Self::Rdjson),
+            "json-diff" => Ok(Self::JsonDiff),
+            _ => Err(format!(
+                "invalid output format '{s}' (expected text, json, tsv, rdjson, or json-diff)"
+            )),
+        }
+    }
+}
+
+/// Line endings of text written to stdout (`--output-line-ending`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputLineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl FromStr for OutputLineEnding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lf" => Ok(Self::Lf),
+            "crlf" => Ok(Self::Crlf),
+            _ => Err(format!(
+                "invalid output line ending '{s}' (expected lf or crlf)"
+            )),
+        }
+    }
+}
+
+/// Writer that turns `\n` into `\r\n`, leaving existing `\r\n` alone
+pub struct CrlfWriter<W> {
+    inner: W,
+    /// Whether the last byte written was `\r`, which may precede a `\n` in the next write
+    after_cr: bool,
+}
+
+impl<W: Write> CrlfWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            after_cr: false,
+        }
+    }
+}
+
+impl<W: Write> Write for CrlfWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut converted = Vec::with_capacity(buf.len() + buf.len() / 16);
+        for &byte in buf {
+            if byte == b'\n' && !self.after_cr {
+                converted.push(b'\r');
+            }
+            converted.push(byte);
+            self.after_cr = byte == b'\r';
+        }
+        self.inner.write_all(&converted)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Why a file was not processed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkipReason {
+    Empty,
+    Binary,
+    NonUtf8,
+    /// UTF-16 text (detected by BOM); convertible with `--convert-utf16`
+    Utf16,
+    /// A large file without any line breaks (e.g. a minified bundle)
+    SingleLongLine,
+    /// File name listed in `skip_filenames` / `--skip-file`
+    Configured,
+    /// More lines than `--skip-over-lines`
+    TooManyLines,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            SkipReason::Empty => "empty",
+            SkipReason::Binary => "binary",
+            SkipReason::NonUtf8 => "non-UTF-8",
+            SkipReason::Utf16 => "UTF-16",
+            SkipReason::SingleLongLine => "single long line",
+            SkipReason::Configured => "configured name",
+            SkipReason::TooManyLines => "too many lines",
+        };
+        f.write_str(reason)
+    }
 }
 
 pub struct Config {
     pub check_only: bool,
     pub output_mode: OutputMode,
     pub normalize: NormalizeConfig,
+    pub walk: WalkOptions,
+    /// Transcode UTF-16 files (with BOM) to UTF-8 instead of skipping them
+    pub convert_utf16: bool,
+    /// Detect the encoding of non-UTF-8 files and transcode them to UTF-8
+    pub auto_encoding: bool,
+    /// Convert stray Windows-1252 bytes in otherwise-UTF-8 files to UTF-8
+    pub repair_cp1252: bool,
+    /// Process files that look binary; they must still decode as UTF-8
+    pub force: bool,
+    /// Number of leading bytes scanned for null bytes (`usize::MAX` scans everything)
+    pub binary_check_bytes: usize,
+    /// File names (not paths) to skip without reading
+    pub skip_filenames: Vec<String>,
+    /// Report `.orig`/`.rej` files left behind by failed merges or patches
+    pub check_artifacts: bool,
+    /// Skip files larger than this many bytes that contain no line breaks (0 = never skip)
+    pub max_single_line_bytes: usize,
+    /// Re-normalize each file until it stops changing (at most `MAX_STABLE_PASSES` passes)
+    pub repeat_until_stable: bool,
+    /// Skip files with more than this many lines without reading them whole
+    pub skip_over_lines: Option<usize>,
+    /// `[rules.*]` scopes that turn detections off for some files
+    pub rule_scopes: Vec<RuleScope>,
+    /// Record the line operations of each change in `FileResult::line_ops`
+    pub record_line_ops: bool,
+    /// Record the hash of each reported file's content in `FileResult::sha256`
+    pub record_sha256: bool,
+    /// Don't fix files that have detections; report them for manual attention instead
+    pub no_write_on_detection: bool,
+    /// Set the executable bit on files with a shebang and clear it elsewhere
+    #[cfg(unix)]
+    pub normalize_mode: bool,
 }
 
 pub struct OutputContext {
@@ -21,6 +191,15 @@ pub struct OutputContext {
     pub colors: Colors,
     pub verbose: bool,
     pub show_progress: bool,
+    /// Cap on full-width space warning lines printed per file (None = all)
+    pub max_warnings_shown: Option<usize>,
+    /// Where per-file output and errors are written
+    pub sink: OutputSink,
+    /// Show paths relative to this directory where possible (`--relative`)
+    pub relative_to: Option<PathBuf>,
+    /// Write warnings, errors and the summary to the error writer, leaving
+    /// the output writer to diffs and file lists (`--diagnostics-to-stderr`)
+    pub diagnostics_to_stderr: bool,
 }
 
 impl OutputContext {
@@ -30,56 +209,211 @@ impl OutputContext {
             colors: Colors::new(use_colors),
             verbose,
             show_progress,
+            max_warnings_shown: None,
+            sink: OutputSink::stdio(),
+            relative_to: None,
+            diagnostics_to_stderr: false,
+        }
+    }
+
+    /// The writer for status, warning and error lines
+    pub(crate) fn diagnostics(&self) -> RefMut<'_, Box<dyn Write>> {
+        if self.diagnostics_to_stderr {
+            self.sink.err()
+        } else {
+            self.sink.out()
+        }
+    }
+
+    /// `path` as shown in output: relative to `relative_to` when it lies
+    /// under it, otherwise unchanged
+    pub fn display_path<'a>(&self, path: &'a Path) -> &'a Path {
+        self.relative_to
+            .as_deref()
+            .and_then(|base| path.strip_prefix(base).ok())
+            .unwrap_or(path)
+    }
+}
+
+/// Destination for run output: stdout and stderr, or writers supplied by an
+/// embedding program.
+pub struct OutputSink {
+    out: RefCell<Box<dyn Write>>,
+    err: RefCell<Box<dyn Write>>,
+}
+
+impl OutputSink {
+    /// Write normal output to `out` and error messages to `err`
+    pub fn new(out: impl Write + 'static, err: impl Write + 'static) -> Self {
+        Self {
+            out: RefCell::new(Box::new(out)),
+            err: RefCell::new(Box::new(err)),
         }
     }
+
+    /// Write to the process's stdout and stderr
+    pub fn stdio() -> Self {
+        Self::new(io::stdout(), io::stderr())
+    }
+
+    pub(crate) fn out(&self) -> RefMut<'_, Box<dyn Write>> {
+        self.out.borrow_mut()
+    }
+
+    pub(crate) fn err(&self) -> RefMut<'_, Box<dyn Write>> {
+        self.err.borrow_mut()
+    }
+}
+
+/// `println!` to the context's output writer. Write errors are ignored:
+/// a closed pipe shouldn't abort the run.
+macro_rules! outln {
+    ($ctx:expr) => {{
+        let _ = writeln!($ctx.sink.out());
+    }};
+    ($ctx:expr, $($arg:tt)*) => {{
+        let _ = writeln!($ctx.sink.out(), $($arg)*);
+    }};
+}
+
+/// `eprintln!` to the context's error writer
+macro_rules! errln {
+    ($ctx:expr, $($arg:tt)*) => {{
+        let _ = writeln!($ctx.sink.err(), $($arg)*);
+    }};
+}
+
+pub(crate) use errln;
+
+/// `println!` to the context's diagnostics writer (stdout unless
+/// `--diagnostics-to-stderr`)
+macro_rules! diagln {
+    ($ctx:expr) => {{
+        let _ = writeln!($ctx.diagnostics());
+    }};
+    ($ctx:expr, $($arg:tt)*) => {{
+        let _ = writeln!($ctx.diagnostics(), $($arg)*);
+    }};
+}
+
+/// Per-file outcome for files that needed changes or had problems
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: PathBuf,
+    /// Content was (or, with `--check`, would be) modified
+    pub changed: bool,
+    pub problems: Vec<Problem>,
+    /// SHA-256 of the content as read, so `--apply-from` can tell if it changed
+    /// since; with `Config::record_sha256`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Edit script for the content change, with `Config::record_line_ops`
+    #[serde(skip)]
+    pub line_ops: Option<Vec<LineOp>>,
+}
+
+/// Serialize a path as a string, replacing bytes that are not valid UTF-8
+fn serialize_path_lossy<S: serde::Serializer>(
+    path: &Path,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// One line of an edit script (`--format json-diff`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineOp {
+    /// `delete`, `insert`, or `equal`
+    pub op: &'static str,
+    /// 1-based; deleted and equal lines count in the original, inserted lines in the result
+    pub line: usize,
+    /// The line without its line break
+    pub text: String,
+}
+
+/// Diff `original` against `content` line by line.
+pub fn line_ops(original: &str, content: &str) -> Vec<LineOp> {
+    TextDiff::from_lines(original, content)
+        .iter_all_changes()
+        .map(|change| {
+            let (op, index) = match change.tag() {
+                ChangeTag::Delete => ("delete", change.old_index()),
+                ChangeTag::Insert => ("insert", change.new_index()),
+                ChangeTag::Equal => ("equal", change.old_index()),
+            };
+            let text = change.value();
+            LineOp {
+                op,
+                line: index.map_or(0, |i| i + 1),
+                text: text.strip_suffix('\n').unwrap_or(text).to_string(),
+            }
+        })
+        .collect()
 }
 
 pub struct RunResult {
     pub files_fixed: usize,
     pub files_with_problems: usize,
+    /// Files with detection-only problems (TODOs, secrets, ...) left in place
+    pub files_with_detections: usize,
     pub warnings: usize,
+    /// Files that couldn't be read or written
+    pub errors: usize,
+    /// Files found by the walk
+    pub files_scanned: usize,
+    /// Files read and normalized (or reported, for patch artifacts)
+    pub files_processed: usize,
+    /// Files skipped as binary, empty, non-UTF-8, configured, or too large
+    pub files_skipped: usize,
+    /// Of the skipped files, those that were not valid UTF-8
+    pub files_non_utf8: usize,
+    pub files: Vec<FileResult>,
 }
 
 impl RunResult {
     pub fn has_problems(&self) -> bool {
         self.files_with_problems > 0
     }
+
+    pub fn has_detections(&self) -> bool {
+        self.files_with_detections > 0
+    }
 }
 
 pub fn print_check_result(
     path: &Path,
     result: &NormalizeResult,
-    _config: &Config,
+    config: &Config,
     ctx: &OutputContext,
 ) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
     if ctx.mode == OutputMode::Quiet {
-        println!("{}", path.display());
+        outln!(ctx, "{}", ctx.display_path(path).display());
+        return;
+    }
+    if ctx.mode == OutputMode::Explain {
+        print_explain(path, result, config, ctx);
         return;
     }
 
-    println!(
+    diagln!(
+        ctx,
         "{}Error:{} {}",
         ctx.colors.error,
         ctx.colors.reset(),
-        path.display()
+        ctx.display_path(path).display()
     );
 
     if result.original != result.content {
         // Check what kind of changes were made
-        if !result.original.ends_with('\n') && result.content.ends_with('\n') {
-            println!("  - missing EOF newline");
+        if result.changed_rules.contains(&Rule::LineEndings) {
+            diagln!(ctx, "  - CRLF/CR line endings");
         }
-
-        // Check for trailing whitespace
-        for (i, (orig_line, _)) in result
-            .original
-            .lines()
-            .zip(result.content.lines())
-            .enumerate()
-        {
-            if orig_line.len() != orig_line.trim_end().len() {
-                println!("  - trailing whitespace at line {}", i + 1);
-            }
+        if result.added_final_newline() {
+            diagln!(ctx, "  - missing EOF newline");
         }
     }
 
@@ -87,42 +421,144 @@ pub fn print_check_result(
     for problem in &result.problems {
         match &problem.kind {
             ProblemKind::FullWidthSpace => {
-                println!("  - full-width space at line {}", problem.line);
+                diagln!(ctx, "  - full-width space at line {}", problem.line);
             }
             ProblemKind::LeadingBlankLines { count } => {
-                println!("  - {} leading blank line(s)", count);
+                diagln!(ctx, "  - {} leading blank line(s)", count);
             }
             ProblemKind::ZeroWidthCharacter => {
-                println!("  - zero-width character at line {}", problem.line);
+                diagln!(ctx, "  - zero-width character at line {}", problem.line);
             }
             ProblemKind::ExcessiveBlankLines { found, limit } => {
-                println!(
+                diagln!(
+                    ctx,
                     "  - {} consecutive blank lines at line {} (limit: {})",
-                    found, problem.line, limit
+                    found,
+                    problem.line,
+                    limit
                 );
             }
             ProblemKind::CodeBlockRemnant => {
-                println!("  - code block remnant at line {}", problem.line);
+                diagln!(ctx, "  - code block remnant at line {}", problem.line);
             }
             // Phase 3: Human Error Prevention
             ProblemKind::TodoComment => {
-                println!("  - TODO comment at line {}", problem.line);
+                diagln!(ctx, "  - TODO comment at line {}", problem.line);
             }
             ProblemKind::FixmeComment => {
-                println!("  - FIXME comment at line {}", problem.line);
+                diagln!(ctx, "  - FIXME comment at line {}", problem.line);
+            }
+            ProblemKind::UnreferencedTodo { marker } => {
+                diagln!(
+                    ctx,
+                    "  - {} without an issue reference at line {}",
+                    marker,
+                    problem.line
+                );
+            }
+            ProblemKind::MisplacedShebang => {
+                diagln!(ctx, "  - shebang not on line 1 at line {}", problem.line);
             }
             ProblemKind::DebugCode { pattern } => {
-                println!("  - debug code '{}' at line {}", pattern, problem.line);
+                diagln!(ctx, "  - debug code '{}' at line {}", pattern, problem.line);
             }
             ProblemKind::SecretPattern { hint } => {
-                println!("  - potential secret ({}) at line {}", hint, problem.line);
+                diagln!(
+                    ctx,
+                    "  - potential secret ({}) at line {}",
+                    hint,
+                    problem.line
+                );
             }
             ProblemKind::LongLine { length, limit } => {
-                println!(
+                diagln!(
+                    ctx,
                     "  - line {} is too long ({} > {} chars)",
-                    problem.line, length, limit
+                    problem.line,
+                    length,
+                    limit
+                );
+            }
+            ProblemKind::InconsistentIndent { found } => {
+                diagln!(
+                    ctx,
+                    "  - line {} is indented by {} spaces (not a multiple of the indent size)",
+                    problem.line,
+                    found
+                );
+            }
+            ProblemKind::FileTooLong { lines, limit } => {
+                diagln!(ctx, "  - file is too long ({} > {} lines)", lines, limit);
+            }
+            ProblemKind::Base64Blob { length } => {
+                diagln!(
+                    ctx,
+                    "  - base64 blob ({} chars) at line {}",
+                    length,
+                    problem.line
+                );
+            }
+            ProblemKind::MissingShebang => {
+                diagln!(ctx, "  - missing shebang (#!) line");
+            }
+            ProblemKind::SmartPunctuation => {
+                diagln!(ctx, "  - smart quotes or dashes at line {}", problem.line);
+            }
+            ProblemKind::ListMarkerSpacing => {
+                diagln!(
+                    ctx,
+                    "  - extra space after list marker at line {}",
+                    problem.line
                 );
             }
+            ProblemKind::ControlWhitespace { .. } => {
+                diagln!(ctx, "  - {} at line {}", problem.kind, problem.line);
+            }
+            ProblemKind::TrailingWhitespace => {
+                diagln!(ctx, "  - trailing whitespace at line {}", problem.line);
+            }
+            ProblemKind::WhitespaceOnlyLine => {
+                diagln!(
+                    ctx,
+                    "  - whitespace-only blank line at line {}",
+                    problem.line
+                );
+            }
+            ProblemKind::MissingHeader => {
+                diagln!(ctx, "  - missing required header");
+            }
+            ProblemKind::TrailingBlankLines { count } => {
+                diagln!(
+                    ctx,
+                    "  - {} trailing blank line(s) at line {}",
+                    count,
+                    problem.line
+                );
+            }
+        }
+    }
+
+    // Preview the fix that would be applied
+    if ctx.mode == OutputMode::Diff && result.has_changes() {
+        write_file_diff(path, &result.original, &result.content, ctx);
+    }
+}
+
+/// One line per rule that would change the file, then one per detection
+fn print_explain(path: &Path, result: &NormalizeResult, config: &Config, ctx: &OutputContext) {
+    outln!(ctx, "{}", ctx.display_path(path).display());
+    for rule in &result.changed_rules {
+        match rule {
+            Rule::LineEndings if result.original.contains("\r\n") => {
+                outln!(ctx, "  {} (crlf)", rule.id());
+            }
+            Rule::LineEndings => outln!(ctx, "  {} (cr)", rule.id()),
+            _ => outln!(ctx, "  {}", rule.id()),
+        }
+    }
+    for problem in &result.problems {
+        if config.normalize.is_unresolved(&problem.kind) {
+            outln!(ctx, "  {} (line {})", problem.kind.rule_id(), problem.line);
         }
     }
 }
@@ -135,91 +571,395 @@ pub fn print_fix_result(
     ctx: &OutputContext,
 ) {
     match ctx.mode {
-        OutputMode::Quiet => println!("{}", path.display()),
-        OutputMode::Diff => print_diff(&path.display().to_string(), original, &result.content),
+        OutputMode::Quiet => outln!(ctx, "{}", ctx.display_path(path).display()),
+        OutputMode::Diff => write_file_diff(path, original, &result.content, ctx),
+        // Explain runs never write, so they don't reach here
+        OutputMode::Structured | OutputMode::Explain => {}
         OutputMode::Normal => {
-            // Print warnings for full-width spaces
-            for problem in result
+            // Print warnings for full-width spaces, up to the configured cap
+            let fullwidth: Vec<_> = result
                 .problems
                 .iter()
                 .filter(|p| matches!(p.kind, ProblemKind::FullWidthSpace))
-            {
-                println!(
+                .collect();
+            let shown = ctx.max_warnings_shown.unwrap_or(usize::MAX);
+            for problem in fullwidth.iter().take(shown) {
+                diagln!(
+                    ctx,
                     "{}Warning:{} {}:{} full-width space",
                     ctx.colors.warning,
                     ctx.colors.reset(),
-                    path.display(),
+                    ctx.display_path(path).display(),
                     problem.line
                 );
             }
-            println!(
+            if fullwidth.len() > shown {
+                diagln!(
+                    ctx,
+                    "{}Warning:{} {}: ... and {} more full-width spaces",
+                    ctx.colors.warning,
+                    ctx.colors.reset(),
+                    ctx.display_path(path).display(),
+                    fullwidth.len() - shown
+                );
+            }
+            diagln!(
+                ctx,
                 "{}Fixed:{} {}",
                 ctx.colors.success,
                 ctx.colors.reset(),
-                path.display()
+                ctx.display_path(path).display()
             );
         }
     }
 }
 
+/// Warn that the paths and filters selected nothing to process
+pub fn print_no_files_matched(ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Quiet {
+        return;
+    }
+    errln!(
+        ctx,
+        "{}No files matched{}",
+        ctx.colors.warning,
+        ctx.colors.reset()
+    );
+}
+
+/// A file left unwritten by `--no-write-on-detection`, with the detections that held it back
+pub fn print_held_back(path: &Path, detections: &[&Problem], ctx: &OutputContext) {
+    if matches!(ctx.mode, OutputMode::Structured | OutputMode::Quiet) {
+        return;
+    }
+    diagln!(
+        ctx,
+        "{}Not fixed:{} {} (manual attention needed)",
+        ctx.colors.warning,
+        ctx.colors.reset(),
+        ctx.display_path(path).display()
+    );
+    for problem in detections {
+        diagln!(ctx, "  - {} at line {}", problem.kind, problem.line);
+    }
+}
+
+pub fn print_mode_change(path: &Path, change: ModeChange, config: &Config, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
+    if ctx.mode == OutputMode::Quiet {
+        return;
+    }
+    if config.check_only {
+        diagln!(
+            ctx,
+            "  - file mode {:o} should be {:o}",
+            change.from,
+            change.to
+        );
+    } else {
+        diagln!(
+            ctx,
+            "{}Mode:{} {} ({:o} -> {:o})",
+            ctx.colors.success,
+            ctx.colors.reset(),
+            ctx.display_path(path).display(),
+            change.from,
+            change.to
+        );
+    }
+}
+
+pub fn print_artifact(path: &Path, config: &Config, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
+    if config.check_only {
+        if ctx.mode == OutputMode::Quiet {
+            outln!(ctx, "{}", ctx.display_path(path).display());
+            return;
+        }
+        diagln!(
+            ctx,
+            "{}Error:{} {}",
+            ctx.colors.error,
+            ctx.colors.reset(),
+            ctx.display_path(path).display()
+        );
+        diagln!(ctx, "  - patch artifact (leftover from a merge or patch)");
+    } else if ctx.mode != OutputMode::Quiet {
+        diagln!(
+            ctx,
+            "{}Warning:{} {}: patch artifact (leftover from a merge or patch)",
+            ctx.colors.warning,
+            ctx.colors.reset(),
+            ctx.display_path(path).display()
+        );
+    }
+}
+
 pub fn print_checked(path: &Path, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
     if ctx.mode == OutputMode::Quiet {
         return;
     }
-    println!(
+    diagln!(
+        ctx,
         "{}Checked:{} {}",
         ctx.colors.info,
         ctx.colors.reset(),
-        path.display()
+        ctx.display_path(path).display()
     );
 }
 
-pub fn print_skipped(path: &Path, reason: &str, ctx: &OutputContext) {
+pub fn print_skipped(path: &Path, reason: SkipReason, config: &Config, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
     if ctx.mode == OutputMode::Quiet {
         return;
     }
-    println!(
-        "{}Skipping {}: {}{}",
+    let hint = if reason == SkipReason::NonUtf8 && !config.auto_encoding {
+        " (try --auto-encoding)"
+    } else {
+        ""
+    };
+    diagln!(
+        ctx,
+        "{}Skipping {}{}: {}{}",
         ctx.colors.info,
         reason,
+        hint,
         ctx.colors.reset(),
-        path.display()
+        ctx.display_path(path).display()
+    );
+}
+
+/// Stray Windows-1252 bytes found (or, when checking, to be fixed) by `--repair-cp1252`
+pub fn print_repaired(path: &Path, count: usize, config: &Config, ctx: &OutputContext) {
+    if matches!(ctx.mode, OutputMode::Structured | OutputMode::Quiet) {
+        return;
+    }
+    let action = if config.check_only {
+        "To repair"
+    } else {
+        "Repaired"
+    };
+    diagln!(
+        ctx,
+        "{}{action} {count} Windows-1252 byte(s):{} {}",
+        ctx.colors.info,
+        ctx.colors.reset(),
+        ctx.display_path(path).display()
+    );
+}
+
+pub fn print_transcoded(path: &Path, encoding: &'static Encoding, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
+    if ctx.mode == OutputMode::Quiet {
+        return;
+    }
+    diagln!(
+        ctx,
+        "{}Detected {}:{} {}",
+        ctx.colors.info,
+        encoding.name(),
+        ctx.colors.reset(),
+        ctx.display_path(path).display()
+    );
+}
+
+/// Render a `--trace`: each rule with whether it changed the content, the
+/// content it changed to and the problems it reported, then every problem of
+/// the run. Content lines are escaped and, like `cat -A`, end in `$` where a
+/// `\n` was, so `\r`, tabs, trailing spaces and a missing final newline show.
+pub fn render_trace(stages: &[TraceStage], result: &NormalizeResult) -> String {
+    let mut trace = String::new();
+    for stage in stages {
+        let state = if stage.changed {
+            "changed"
+        } else {
+            "unchanged"
+        };
+        trace.push_str(&format!("{}: {state}\n", stage.rule.id()));
+        if stage.changed {
+            for line in stage.content.split_inclusive('\n') {
+                let (text, end) = match line.strip_suffix('\n') {
+                    Some(text) => (text, "$"),
+                    None => (line, ""),
+                };
+                trace.push_str(&format!("  | {}{end}\n", text.escape_debug()));
+            }
+        }
+        for problem in &stage.problems {
+            trace.push_str(&format!("  - {} at line {}\n", problem.kind, problem.line));
+        }
+    }
+    trace.push_str(&format!("problems: {}\n", result.problems.len()));
+    for problem in &result.problems {
+        trace.push_str(&format!("  - {} at line {}\n", problem.kind, problem.line));
+    }
+    trace
+}
+
+/// Problem kinds shown as errors in the stats table; the rest are warnings
+const ERROR_KINDS: &[&str] = &["secret-pattern", "base64-blob"];
+
+/// Render per-kind problem counts as two aligned columns: kind names
+/// left-aligned, counts right-aligned and colored by severity.
+pub fn render_stats_table(stats: &BTreeMap<&str, usize>, colors: Colors) -> String {
+    let kind_width = stats.keys().map(|kind| kind.len()).max().unwrap_or(0);
+    let count_width = stats
+        .values()
+        .map(|count| count.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut table = String::new();
+    for (kind, count) in stats {
+        let color = if ERROR_KINDS.contains(kind) {
+            colors.error
+        } else {
+            colors.warning
+        };
+        table.push_str(&format!(
+            "{kind:<kind_width$}  {color}{count:>count_width$}{}\n",
+            colors.reset()
+        ));
+    }
+    table
+}
+
+pub fn print_diff(label: &str, original: &str, content: &str, colors: Colors) {
+    let mut stdout = io::stdout().lock();
+    // A closed pipe shouldn't abort the run; the diff is informational
+    let _ = write_diff(&mut stdout, label, original, content, colors);
+}
+
+/// Write one file's diff to the context's output as a single block.
+///
+/// The diff is rendered in full before anything is written, so a file's
+/// `---`/`+++` header and hunks are never split by other output.
+fn write_file_diff(path: &Path, original: &str, content: &str, ctx: &OutputContext) {
+    let mut diff = Vec::new();
+    let _ = write_diff(
+        &mut diff,
+        &ctx.display_path(path).display().to_string(),
+        original,
+        content,
+        ctx.colors,
     );
+    let mut out = ctx.sink.out();
+    let _ = out.write_all(&diff).and_then(|()| out.flush());
 }
 
-pub fn print_diff(label: &str, original: &str, content: &str) {
+/// Write a unified diff with deletions in red, insertions in green, and hunk headers in cyan.
+pub fn write_diff(
+    w: &mut impl Write,
+    label: &str,
+    original: &str,
+    content: &str,
+    colors: Colors,
+) -> io::Result<()> {
     let diff = TextDiff::from_lines(original, content);
 
-    println!("--- {label}");
-    println!("+++ {label}");
+    writeln!(w, "--- {label}")?;
+    writeln!(w, "+++ {label}")?;
+
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        writeln!(w, "{}{}{}", colors.info, hunk.header(), colors.reset())?;
 
-    for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
-        if idx > 0 {
-            println!();
+        for change in hunk.iter_changes() {
+            let (sign, color) = match change.tag() {
+                ChangeTag::Delete => ('-', colors.error),
+                ChangeTag::Insert => ('+', colors.success),
+                ChangeTag::Equal => (' ', ""),
+            };
+            let line = change.value();
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text, "\n"),
+                None => (line, ""),
+            };
+            let reset = if color.is_empty() { "" } else { colors.reset() };
+            write!(w, "{color}{sign}{text}{reset}{newline}")?;
         }
+    }
 
-        for op in group {
-            for change in diff.iter_changes(op) {
-                let sign = match change.tag() {
-                    ChangeTag::Delete => '-',
-                    ChangeTag::Insert => '+',
-                    ChangeTag::Equal => ' ',
-                };
-                print!("{sign}{change}");
-            }
+    Ok(())
+}
+
+/// Count the problems of a run left to the user, e.g. `13 problems (12 TODOs, 1 secret)`.
+///
+/// Kinds are listed from most to least frequent. Returns `None` if there are none.
+fn manual_attention_tally(result: &RunResult, config: &NormalizeConfig) -> Option<String> {
+    let mut counts: BTreeMap<&'static str, usize> = BTreeMap::new();
+    for problem in result.files.iter().flat_map(|f| &f.problems) {
+        if config.is_unresolved(&problem.kind) {
+            *counts.entry(problem.kind.rule_id()).or_insert(0) += 1;
         }
     }
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    // Stable sort keeps ties in rule id order
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let kinds: Vec<String> = counts
+        .iter()
+        .map(|&(rule_id, count)| format!("{count} {}", tally_noun(rule_id, count)))
+        .collect();
+    let problems = if total == 1 { "problem" } else { "problems" };
+    Some(format!("{total} {problems} ({})", kinds.join(", ")))
+}
+
+/// How the manual attention tally names `count` problems of a rule
+fn tally_noun(rule_id: &str, count: usize) -> &'static str {
+    let (one, many) = match rule_id {
+        "todo-comment" => ("TODO", "TODOs"),
+        "fixme-comment" => ("FIXME", "FIXMEs"),
+        "debug-code" => ("debug statement", "debug statements"),
+        "secret-pattern" => ("secret", "secrets"),
+        "long-line" => ("long line", "long lines"),
+        "inconsistent-indent" => ("misindented line", "misindented lines"),
+        "file-too-long" => ("file too long", "files too long"),
+        "base64-blob" => ("base64 blob", "base64 blobs"),
+        "missing-shebang" => ("missing shebang", "missing shebangs"),
+        "unreferenced-todo" => ("unreferenced TODO", "unreferenced TODOs"),
+        "fullwidth-space" => ("full-width space", "full-width spaces"),
+        "missing-header" => ("missing header", "missing headers"),
+        _ => ("other problem", "other problems"),
+    };
+    if count == 1 {
+        one
+    } else {
+        many
+    }
 }
 
 pub fn print_summary(result: &RunResult, config: &Config, ctx: &OutputContext) {
+    if ctx.mode == OutputMode::Structured {
+        return;
+    }
+    // Keep explain output one entry per line for grep
+    if ctx.mode == OutputMode::Explain {
+        return;
+    }
     if ctx.mode == OutputMode::Quiet {
         return;
     }
 
     if config.check_only {
         if result.files_with_problems > 0 {
-            println!();
-            println!(
+            diagln!(ctx);
+            diagln!(
+                ctx,
                 "{}{} files with problems{}",
                 ctx.colors.error,
                 result.files_with_problems,
@@ -227,7 +967,7 @@ pub fn print_summary(result: &RunResult, config: &Config, ctx: &OutputContext) {
             );
         }
     } else if result.files_fixed > 0 || result.warnings > 0 {
-        println!();
+        diagln!(ctx);
         let mut parts = vec![];
         if result.files_fixed > 0 {
             parts.push(format!(
@@ -245,6 +985,164 @@ pub fn print_summary(result: &RunResult, config: &Config, ctx: &OutputContext) {
                 ctx.colors.reset()
             ));
         }
-        println!("{}", parts.join(", "));
+        diagln!(ctx, "{}", parts.join(", "));
+    }
+
+    // Detections survive fixing; tally what is left for the user
+    if !config.check_only {
+        if let Some(tally) = manual_attention_tally(result, &config.normalize) {
+            if result.files_fixed == 0 && result.warnings == 0 {
+                diagln!(ctx);
+            }
+            diagln!(
+                ctx,
+                "{}Manual attention needed: {tally}{}",
+                ctx.colors.warning,
+                ctx.colors.reset()
+            );
+        }
+    }
+
+    if result.files_non_utf8 > 0 && !config.auto_encoding {
+        diagln!(
+            ctx,
+            "{}{} files skipped as non-UTF-8; rerun with --auto-encoding to transcode{}",
+            ctx.colors.warning,
+            result.files_non_utf8,
+            ctx.colors.reset()
+        );
+    }
+
+    if ctx.verbose {
+        diagln!(
+            ctx,
+            "Processed {} of {} files ({} skipped)",
+            result.files_processed,
+            result.files_scanned,
+            result.files_skipped
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff_string(original: &str, content: &str, colors: Colors) -> String {
+        let mut out = Vec::new();
+        write_diff(&mut out, "test.txt", original, content, colors).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_diff_colored_when_enabled() {
+        let diff = diff_string("hello  \n", "hello\n", Colors::new(true));
+
+        assert!(diff.contains("\x1b[31m-hello  \x1b[0m\n"));
+        assert!(diff.contains("\x1b[32m+hello\x1b[0m\n"));
+        assert!(diff.contains("\x1b[36m@@ -1 +1 @@\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_diff_plain_when_disabled() {
+        let diff = diff_string("hello  \n", "hello\n", Colors::new(false));
+
+        assert!(!diff.contains('\x1b'));
+        assert_eq!(
+            diff,
+            "--- test.txt\n+++ test.txt\n@@ -1 +1 @@\n-hello  \n+hello\n"
+        );
+    }
+
+    fn sample_stats() -> BTreeMap<&'static str, usize> {
+        BTreeMap::from([
+            ("secret-pattern", 2),
+            ("todo-comment", 113),
+            ("long-line", 9),
+        ])
+    }
+
+    #[test]
+    fn test_stats_table_aligns_columns() {
+        let table = render_stats_table(&sample_stats(), Colors::new(false));
+        let rows: Vec<&str> = table.lines().collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                "long-line         9",
+                "secret-pattern    2",
+                "todo-comment    113",
+            ]
+        );
+        // Counts are right-aligned, so every row ends at the same column
+        assert!(rows.iter().all(|row| row.len() == rows[0].len()));
+    }
+
+    #[test]
+    fn test_stats_table_colors_by_severity() {
+        let table = render_stats_table(&sample_stats(), Colors::new(true));
+        assert!(table.contains("secret-pattern  \x1b[31m  2\x1b[0m"));
+        assert!(table.contains("todo-comment    \x1b[33m113\x1b[0m"));
+
+        let plain = render_stats_table(&sample_stats(), Colors::new(false));
+        assert!(!plain.contains('\x1b'));
+    }
+
+    // ===========================================
+    // Line Operations
+    // ===========================================
+
+    #[test]
+    fn test_line_ops_number_lines_per_side() {
+        let ops = line_ops("a\nb  \nc", "a\nb\nc\n");
+        let summary: Vec<(&str, usize, &str)> = ops
+            .iter()
+            .map(|op| (op.op, op.line, op.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("equal", 1, "a"),
+                ("delete", 2, "b  "),
+                ("delete", 3, "c"),
+                ("insert", 2, "b"),
+                ("insert", 3, "c"),
+            ]
+        );
+    }
+
+    // ===========================================
+    // Relative Paths
+    // ===========================================
+
+    #[test]
+    fn test_display_path_strips_base_or_falls_back() {
+        let ctx = OutputContext {
+            relative_to: Some(PathBuf::from("/work/repo")),
+            ..OutputContext::new(OutputMode::Normal, false, false, false)
+        };
+        assert_eq!(
+            ctx.display_path(Path::new("/work/repo/src/main.rs")),
+            Path::new("src/main.rs")
+        );
+        assert_eq!(
+            ctx.display_path(Path::new("/elsewhere/a.txt")),
+            Path::new("/elsewhere/a.txt")
+        );
+        assert_eq!(ctx.display_path(Path::new("a.txt")), Path::new("a.txt"));
+    }
+
+    // ===========================================
+    // Output Line Endings
+    // ===========================================
+
+    #[test]
+    fn test_crlf_writer_converts_lf_only() {
+        let mut writer = CrlfWriter::new(Vec::new());
+        writer.write_all(b"a\nb\r\nc\r").unwrap();
+        // A \r\n split across writes is not doubled
+        writer.write_all(b"\nd\n").unwrap();
+        assert_eq!(writer.inner, b"a\r\nb\r\nc\r\nd\r\n");
     }
 }
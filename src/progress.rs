@@ -1,7 +1,60 @@
 use indicatif::{ProgressBar, ProgressStyle};
+use std::ffi::OsString;
+use std::time::Duration;
 
 const PROGRESS_THRESHOLD: u64 = 10;
 
+/// Bar layout: spinner, bar, position, throughput, ETA, current file name
+const PROGRESS_TEMPLATE: &str =
+    "{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({per_sec}, ETA {eta}) {msg}";
+
+/// Spinner redraw interval, so it keeps animating during slow IO
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Environment variables set by common CI services
+const CI_ENV_VARS: &[&str] = &[
+    "CI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TRAVIS",
+    "JENKINS_URL",
+    "TF_BUILD",
+    "TEAMCITY_VERSION",
+];
+
+/// Whether we appear to be running under a CI service
+pub fn is_ci() -> bool {
+    ci_detected(|name| std::env::var_os(name))
+}
+
+fn ci_detected(var: impl Fn(&str) -> Option<OsString>) -> bool {
+    CI_ENV_VARS.iter().any(|name| {
+        var(name).is_some_and(|value| !value.is_empty() && value != "false" && value != "0")
+    })
+}
+
+/// Decide whether to draw the progress bar.
+///
+/// Priority: --quiet > --no-progress > --progress > CI detection > TTY detection.
+/// Some CI services attach a pseudo-TTY, so a terminal alone isn't enough.
+pub fn should_show_progress(
+    quiet: bool,
+    no_progress: bool,
+    force_progress: bool,
+    in_ci: bool,
+    is_terminal: bool,
+) -> bool {
+    if quiet || no_progress {
+        return false;
+    }
+    if force_progress {
+        return true;
+    }
+    !in_ci && is_terminal
+}
+
 pub struct ProgressReporter {
     bar: Option<ProgressBar>,
 }
@@ -15,10 +68,11 @@ impl ProgressReporter {
         let bar = ProgressBar::new(total);
         bar.set_style(
             ProgressStyle::default_bar()
-                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+                .template(PROGRESS_TEMPLATE)
                 .unwrap()
                 .progress_chars("=>-"),
         );
+        bar.enable_steady_tick(TICK_INTERVAL);
         Self { bar: Some(bar) }
     }
 
@@ -40,3 +94,71 @@ impl ProgressReporter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_includes_eta_and_rate() {
+        assert!(PROGRESS_TEMPLATE.contains("{eta}"));
+        assert!(PROGRESS_TEMPLATE.contains("{per_sec}"));
+        assert!(ProgressStyle::with_template(PROGRESS_TEMPLATE).is_ok());
+    }
+
+    #[test]
+    fn test_progress_disabled_below_threshold() {
+        let reporter = ProgressReporter::new(PROGRESS_THRESHOLD - 1, true);
+        assert!(reporter.bar.is_none());
+    }
+
+    #[test]
+    fn test_progress_enabled_constructs_bar() {
+        let reporter = ProgressReporter::new(PROGRESS_THRESHOLD, true);
+        assert!(reporter.bar.is_some());
+        reporter.inc();
+        reporter.finish();
+    }
+
+    // ===========================================
+    // Progress Decision
+    // ===========================================
+
+    #[test]
+    fn test_progress_shown_on_terminal_outside_ci() {
+        assert!(should_show_progress(false, false, false, false, true));
+        assert!(!should_show_progress(false, false, false, false, false));
+    }
+
+    #[test]
+    fn test_progress_hidden_in_ci_unless_forced() {
+        assert!(!should_show_progress(false, false, false, true, true));
+        assert!(should_show_progress(false, false, true, true, true));
+        assert!(should_show_progress(false, false, true, false, false));
+    }
+
+    #[test]
+    fn test_quiet_and_no_progress_win_over_progress() {
+        assert!(!should_show_progress(true, false, true, false, true));
+        assert!(!should_show_progress(false, true, true, false, true));
+    }
+
+    #[test]
+    fn test_ci_detected_from_env_vars() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| OsString::from(v))
+            }
+        };
+
+        assert!(ci_detected(env(&[("CI", "true")])));
+        assert!(ci_detected(env(&[("GITHUB_ACTIONS", "true")])));
+        assert!(ci_detected(env(&[("JENKINS_URL", "http://ci")])));
+        assert!(!ci_detected(env(&[])));
+        assert!(!ci_detected(env(&[("CI", "false")])));
+        assert!(!ci_detected(env(&[("CI", "")])));
+        assert!(!ci_detected(env(&[("HOME", "/root")])));
+    }
+}
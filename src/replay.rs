@@ -0,0 +1,139 @@
+//! Applying the fixes of a saved JSON report (`--apply-from <REPORT>`)
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::atomic::write_atomic;
+use crate::normalize::{normalize_file_content, NormalizeConfig};
+
+/// The parts of a `--report` / `--json` document needed to replay it
+#[derive(Deserialize)]
+struct SavedReport {
+    files: Vec<SavedFile>,
+    config: NormalizeConfig,
+}
+
+#[derive(Deserialize)]
+struct SavedFile {
+    path: PathBuf,
+    changed: bool,
+    /// Missing in reports written before hashes were recorded
+    sha256: Option<String>,
+}
+
+/// What `apply_report` did with one file listed as changed
+#[derive(Debug, PartialEq)]
+pub enum ReplayOutcome {
+    /// Re-normalized and written back
+    Fixed,
+    /// Already normalized; nothing to write
+    Clean,
+    /// The content no longer matches the hash in the report
+    ChangedSinceReport,
+    /// The report has no hash to verify the file against
+    NoHash,
+    /// The file is no longer valid UTF-8, so it can't be hashed or normalized
+    NotUtf8,
+}
+
+/// Hex SHA-256 of file content, as recorded in reports
+pub fn content_sha256(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Re-normalize the files a report lists as changed, using the report's
+/// configuration, and write them back. Files whose content no longer matches
+/// the recorded hash are left alone.
+pub fn apply_report(report: &Path) -> io::Result<Vec<(PathBuf, io::Result<ReplayOutcome>)>> {
+    let json = fs::read_to_string(report)?;
+    let report: SavedReport =
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    Ok(report
+        .files
+        .into_iter()
+        .filter(|file| file.changed)
+        .map(|file| {
+            let outcome = replay_file(&file, &report.config);
+            (file.path, outcome)
+        })
+        .collect())
+}
+
+fn replay_file(file: &SavedFile, config: &NormalizeConfig) -> io::Result<ReplayOutcome> {
+    let Some(expected) = &file.sha256 else {
+        return Ok(ReplayOutcome::NoHash);
+    };
+    let Ok(content) = String::from_utf8(fs::read(&file.path)?) else {
+        return Ok(ReplayOutcome::NotUtf8);
+    };
+    if content_sha256(&content) != *expected {
+        return Ok(ReplayOutcome::ChangedSinceReport);
+    }
+
+    let result = normalize_file_content(&file.path, &content, config);
+    if !result.has_changes() {
+        return Ok(ReplayOutcome::Clean);
+    }
+    write_atomic(&file.path, result.content.as_bytes())?;
+    Ok(ReplayOutcome::Fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_report(dir: &Path, path: &Path, sha256: &str) -> PathBuf {
+        let report = serde_json::json!({
+            "files": [{ "path": path, "changed": true, "problems": [], "sha256": sha256 }],
+            "config": NormalizeConfig::default(),
+        });
+        let report_path = dir.join("report.json");
+        fs::write(&report_path, report.to_string()).unwrap();
+        report_path
+    }
+
+    #[test]
+    fn test_content_sha256_is_hex_digest() {
+        assert_eq!(
+            content_sha256(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_apply_report_fixes_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "hello  ").unwrap();
+        let report = write_report(dir.path(), &file, &content_sha256("hello  "));
+
+        let outcomes = apply_report(&report).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].1.as_ref().unwrap(), &ReplayOutcome::Fixed);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello\n");
+    }
+
+    #[test]
+    fn test_apply_report_skips_files_edited_since() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        let report = write_report(dir.path(), &file, &content_sha256("hello  "));
+        fs::write(&file, "edited  ").unwrap();
+
+        let outcomes = apply_report(&report).unwrap();
+        assert_eq!(
+            outcomes[0].1.as_ref().unwrap(),
+            &ReplayOutcome::ChangedSinceReport
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "edited  ");
+    }
+}
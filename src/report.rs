@@ -0,0 +1,335 @@
+//! Aggregate JSON run report (`--report <PATH>`)
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::normalize::NormalizeConfig;
+use crate::output::{FileResult, RunResult};
+
+#[derive(Serialize)]
+struct Report<'a> {
+    files_fixed: usize,
+    files_with_problems: usize,
+    warnings: usize,
+    errors: usize,
+    /// Problem counts keyed by rule id (sorted for stable output)
+    problems_by_kind: BTreeMap<&'static str, usize>,
+    files: &'a [FileResult],
+    config: &'a NormalizeConfig,
+}
+
+/// Count the problems of a finished run by rule id.
+pub fn problem_counts(result: &RunResult) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for problem in result.files.iter().flat_map(|f| &f.problems) {
+        *counts.entry(problem.kind.rule_id()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Build the JSON report for a finished run.
+pub fn build_report(
+    result: &RunResult,
+    config: &NormalizeConfig,
+) -> serde_json::Result<serde_json::Value> {
+    let problems_by_kind = problem_counts(result);
+
+    let report = Report {
+        files_fixed: result.files_fixed,
+        files_with_problems: result.files_with_problems,
+        warnings: result.warnings,
+        errors: result.errors,
+        problems_by_kind,
+        files: &result.files,
+        config,
+    };
+    serde_json::to_value(report)
+}
+
+/// Terse one-line summary printed by `--quiet --json`
+#[derive(Serialize)]
+struct Summary {
+    fixed: usize,
+    /// Files that still have problems after the run
+    problems: usize,
+    errors: usize,
+}
+
+/// Render the compact `{"fixed":N,"problems":M,"errors":K}` summary for a finished run.
+///
+/// In check mode every file with problems still has them; when fixing, only
+/// detection-only problems remain.
+pub fn build_summary(result: &RunResult, check_only: bool) -> String {
+    let summary = Summary {
+        fixed: result.files_fixed,
+        problems: if check_only {
+            result.files_with_problems
+        } else {
+            result.files_with_detections
+        },
+        errors: result.errors,
+    };
+    serde_json::to_string(&summary).expect("summary is always serializable")
+}
+
+/// Render every problem as tab-separated values under a `path\tline\tkind\tdetail` header.
+pub fn build_tsv(result: &RunResult) -> String {
+    let mut tsv = String::from("path\tline\tkind\tdetail\n");
+    for file in &result.files {
+        let path = tsv_field(&file.path.display().to_string());
+        for problem in &file.problems {
+            tsv.push_str(&format!(
+                "{path}\t{}\t{}\t{}\n",
+                problem.line,
+                problem.kind.rule_id(),
+                tsv_field(&problem.kind.to_string())
+            ));
+        }
+    }
+    tsv
+}
+
+/// Build a reviewdog Diagnostic Result (rdjson) with one diagnostic per problem.
+pub fn build_rdjson(result: &RunResult) -> serde_json::Value {
+    let diagnostics: Vec<_> = result
+        .files
+        .iter()
+        .flat_map(|file| file.problems.iter().map(move |problem| (file, problem)))
+        .map(|(file, problem)| {
+            json!({
+                "message": problem.kind.to_string(),
+                "location": {
+                    "path": file.path.display().to_string(),
+                    "range": { "start": { "line": problem.line } },
+                },
+                "severity": problem.kind.severity().as_str(),
+                "code": { "value": problem.kind.rule_id() },
+            })
+        })
+        .collect();
+
+    json!({
+        "source": { "name": "fini", "url": env!("CARGO_PKG_REPOSITORY") },
+        "diagnostics": diagnostics,
+    })
+}
+
+/// Build the `--format json-diff` document: the path and line operations of each changed file.
+pub fn build_json_diff(result: &RunResult) -> serde_json::Value {
+    let files: Vec<_> = result
+        .files
+        .iter()
+        .filter_map(|file| {
+            let ops = file.line_ops.as_ref()?;
+            Some(json!({ "path": file.path.display().to_string(), "ops": ops }))
+        })
+        .collect();
+    serde_json::Value::Array(files)
+}
+
+/// Render problems grouped by rule id: a `kind (count)` heading per kind,
+/// followed by one indented `path:line` per location.
+pub fn build_grouped(result: &RunResult) -> String {
+    let mut groups: BTreeMap<&'static str, Vec<String>> = BTreeMap::new();
+    for file in &result.files {
+        for problem in &file.problems {
+            groups
+                .entry(problem.kind.rule_id())
+                .or_default()
+                .push(format!("{}:{}", file.path.display(), problem.line));
+        }
+    }
+
+    let mut out = String::new();
+    for (kind, locations) in groups {
+        out.push_str(&format!("{kind} ({})\n", locations.len()));
+        for location in locations {
+            out.push_str(&format!("  {location}\n"));
+        }
+    }
+    out
+}
+
+/// Escape characters that would break the row/column structure
+fn tsv_field(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Write the JSON report for a finished run to `path`.
+pub fn write_report(path: &Path, result: &RunResult, config: &NormalizeConfig) -> io::Result<()> {
+    let report = build_report(result, config).map_err(io::Error::other)?;
+    let json = serde_json::to_string_pretty(&report).map_err(io::Error::other)?;
+    fs::write(path, format!("{json}\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize::{Problem, ProblemKind};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_build_report_counts_problems_by_kind() {
+        let result = RunResult {
+            files_fixed: 1,
+            files_with_problems: 0,
+            files_with_detections: 1,
+            warnings: 0,
+            errors: 0,
+            files_scanned: 0,
+            files_processed: 0,
+            files_skipped: 0,
+            files_non_utf8: 0,
+            files: vec![FileResult {
+                path: PathBuf::from("src/main.rs"),
+                changed: true,
+                problems: vec![
+                    Problem {
+                        line: 1,
+                        kind: ProblemKind::TodoComment,
+                    },
+                    Problem {
+                        line: 4,
+                        kind: ProblemKind::TodoComment,
+                    },
+                ],
+                sha256: None,
+                line_ops: None,
+            }],
+        };
+
+        let report = build_report(&result, &NormalizeConfig::default()).unwrap();
+
+        assert_eq!(report["files_fixed"], 1);
+        assert_eq!(report["problems_by_kind"]["todo-comment"], 2);
+        assert_eq!(report["files"][0]["path"], "src/main.rs");
+        assert_eq!(report["files"][0]["problems"][1]["line"], 4);
+        assert_eq!(report["files"][0]["problems"][1]["kind"], "todo-comment");
+        assert_eq!(report["config"]["detect_todos"], true);
+    }
+
+    #[test]
+    fn test_build_rdjson_diagnostics() {
+        let result = RunResult {
+            files_fixed: 0,
+            files_with_problems: 1,
+            files_with_detections: 1,
+            warnings: 0,
+            errors: 0,
+            files_scanned: 0,
+            files_processed: 0,
+            files_skipped: 0,
+            files_non_utf8: 0,
+            files: vec![FileResult {
+                path: PathBuf::from("src/app.js"),
+                changed: false,
+                problems: vec![
+                    Problem {
+                        line: 3,
+                        kind: ProblemKind::TodoComment,
+                    },
+                    Problem {
+                        line: 5,
+                        kind: ProblemKind::SecretPattern {
+                            hint: "API key".to_string(),
+                        },
+                    },
+                ],
+                sha256: None,
+                line_ops: None,
+            }],
+        };
+
+        let rdjson = build_rdjson(&result);
+
+        assert_eq!(rdjson["source"]["name"], "fini");
+        let todo = &rdjson["diagnostics"][0];
+        assert_eq!(todo["message"], "TODO comment");
+        assert_eq!(todo["location"]["path"], "src/app.js");
+        assert_eq!(todo["location"]["range"]["start"]["line"], 3);
+        assert_eq!(todo["severity"], "WARNING");
+        assert_eq!(todo["code"]["value"], "todo-comment");
+        assert_eq!(rdjson["diagnostics"][1]["severity"], "ERROR");
+    }
+
+    #[test]
+    fn test_build_tsv_rows_per_problem() {
+        let result = RunResult {
+            files_fixed: 0,
+            files_with_problems: 1,
+            files_with_detections: 1,
+            warnings: 0,
+            errors: 0,
+            files_scanned: 0,
+            files_processed: 0,
+            files_skipped: 0,
+            files_non_utf8: 0,
+            files: vec![FileResult {
+                path: PathBuf::from("src/app.js"),
+                changed: false,
+                problems: vec![
+                    Problem {
+                        line: 3,
+                        kind: ProblemKind::TodoComment,
+                    },
+                    Problem {
+                        line: 7,
+                        kind: ProblemKind::DebugCode {
+                            pattern: "a\tb\nc".to_string(),
+                        },
+                    },
+                ],
+                sha256: None,
+                line_ops: None,
+            }],
+        };
+
+        let tsv = build_tsv(&result);
+        let rows: Vec<&str> = tsv.lines().collect();
+
+        assert_eq!(rows[0], "path\tline\tkind\tdetail");
+        assert_eq!(rows[1], "src/app.js\t3\ttodo-comment\tTODO comment");
+        // Tabs and newlines in the detail are escaped, keeping one row per problem
+        assert_eq!(rows[2], "src/app.js\t7\tdebug-code\tdebug code 'a\\tb\\nc'");
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[test]
+    fn test_build_summary_is_compact() {
+        let result = RunResult {
+            files_fixed: 3,
+            files_with_problems: 0,
+            files_with_detections: 1,
+            warnings: 2,
+            errors: 1,
+            files_scanned: 0,
+            files_processed: 0,
+            files_skipped: 0,
+            files_non_utf8: 0,
+            files: vec![],
+        };
+
+        assert_eq!(
+            build_summary(&result, false),
+            r#"{"fixed":3,"problems":1,"errors":1}"#
+        );
+
+        let result = RunResult {
+            files_with_problems: 4,
+            ..result
+        };
+        assert_eq!(
+            build_summary(&result, true),
+            r#"{"fixed":3,"problems":4,"errors":1}"#
+        );
+    }
+}
@@ -1,28 +1,196 @@
-use ignore::WalkBuilder;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::GitignoreBuilder;
+use ignore::{Match, WalkBuilder};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+/// Options controlling which files are yielded by the walker
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// When non-empty, only files matching one of these globs are yielded
+    pub includes: GlobSet,
+    /// Files matching any of these globs are skipped
+    pub excludes: GlobSet,
+    /// Directories whose name matches any of these globs are pruned without descending
+    pub exclude_dirs: GlobSet,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            includes: GlobSet::empty(),
+            excludes: GlobSet::empty(),
+            exclude_dirs: GlobSet::empty(),
+        }
+    }
+}
+
+/// Compile glob patterns into a single matcher
+pub fn build_globset(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid glob '{pattern}': {e}"),
+            )
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+/// Read newline-delimited glob patterns from a file.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_patterns_file(path: &Path) -> io::Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Check a file against a glob set, by its path relative to the walk root or its name
+fn matches_globset(globs: &GlobSet, root: &Path, path: &Path) -> bool {
+    if globs.is_empty() {
+        return false;
+    }
+    globs.is_match(glob_candidate(root, path))
+        || path.file_name().is_some_and(|name| globs.is_match(name))
+}
+
+/// Whether a file passes the `--include` and `--exclude` globs
+fn is_selected(options: &WalkOptions, root: &Path, path: &Path) -> bool {
+    let included = options.includes.is_empty() || matches_globset(&options.includes, root, path);
+    included && !matches_globset(&options.excludes, root, path)
+}
+
+/// The path matched against include and exclude globs: relative to the walk root when it is
+/// inside it, always with `/` separators so patterns like `src/*.rs` behave the
+/// same on Windows (including UNC roots such as `\\server\share`).
+fn glob_candidate(root: &Path, path: &Path) -> String {
+    let root = normalize_separators(&root.to_string_lossy());
+    let path = normalize_separators(&path.to_string_lossy());
+    let prefix = if root.ends_with('/') {
+        root
+    } else {
+        format!("{root}/")
+    };
+    match path.strip_prefix(&prefix) {
+        Some(relative) => relative.to_string(),
+        None => path,
+    }
+}
+
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// An error hit while walking, with the path it concerns when known
+#[derive(Debug)]
+pub struct WalkError {
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl WalkError {
+    fn not_found(path: &Path) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            message: "No such file or directory".to_string(),
+        }
+    }
+
+    fn from_ignore(err: &ignore::Error) -> Self {
+        match err {
+            ignore::Error::WithPath { path, err } => Self {
+                path: Some(path.clone()),
+                message: err.to_string(),
+            },
+            ignore::Error::WithDepth { err, .. } => Self::from_ignore(err),
+            ignore::Error::Loop { child, .. } => Self {
+                path: Some(child.clone()),
+                message: err.to_string(),
+            },
+            _ => Self {
+                path: None,
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for WalkError {}
 
 /// Walk paths and yield file paths, respecting gitignore
-pub fn walk_paths(paths: &[String]) -> impl Iterator<Item = io::Result<PathBuf>> {
+pub fn walk_paths(paths: &[String]) -> impl Iterator<Item = Result<PathBuf, WalkError>> {
+    walk_paths_with(paths, &WalkOptions::default())
+}
+
+/// Walk paths with the given options and yield file paths, respecting gitignore
+pub fn walk_paths_with(
+    paths: &[String],
+    options: &WalkOptions,
+) -> impl Iterator<Item = Result<PathBuf, WalkError>> {
     let mut all_files = vec![];
+    // Overlapping paths (e.g. `.` and `./src`) must yield each file once;
+    // dedup on the canonical path but keep the path as walked for display
+    let mut seen = HashSet::new();
 
     for path in paths {
+        let root = Path::new(path);
+        // A mistyped argument is reported on its own; the other paths are still walked
+        if matches!(fs::symlink_metadata(root), Err(e) if e.kind() == io::ErrorKind::NotFound) {
+            all_files.push(Err(WalkError::not_found(root)));
+            continue;
+        }
+        let exclude_dirs = options.exclude_dirs.clone();
         let walker = WalkBuilder::new(path)
             .hidden(true) // Skip hidden files
             .git_ignore(true) // Respect .gitignore
             .git_global(true)
             .git_exclude(true)
+            // Name order makes the file order, and so the output, the same on every filesystem
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                // Prune excluded directories (but never the walk root itself)
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                !(is_dir && entry.depth() > 0 && exclude_dirs.is_match(entry.file_name()))
+            })
             .build();
 
         for entry in walker {
             match entry {
                 Ok(entry) => {
-                    if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
-                        all_files.push(Ok(entry.into_path()));
+                    if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false)
+                        && is_selected(options, root, entry.path())
+                    {
+                        let key = fs::canonicalize(entry.path())
+                            .unwrap_or_else(|_| entry.path().to_path_buf());
+                        if seen.insert(key) {
+                            all_files.push(Ok(entry.into_path()));
+                        }
                     }
                 }
                 Err(e) => {
-                    all_files.push(Err(io::Error::other(e.to_string())));
+                    all_files.push(Err(WalkError::from_ignore(&e)));
                 }
             }
         }
@@ -31,6 +199,115 @@ pub fn walk_paths(paths: &[String]) -> impl Iterator<Item = io::Result<PathBuf>>
     all_files.into_iter()
 }
 
+/// Why walking a root would not yield a particular path
+#[derive(Debug, Clone, PartialEq)]
+pub enum WalkSkip {
+    /// A path component below the root is hidden (starts with `.`)
+    Hidden(String),
+    /// Matched a pattern in an ignore file (`.gitignore`, `.ignore`, `.git/info/exclude`)
+    Ignored { pattern: String, source: PathBuf },
+    /// Inside a directory matching an `--exclude-dir` glob
+    ExcludedDir(String),
+    /// Matched an `--exclude` glob
+    Excluded,
+    /// Matched none of the `--include` globs
+    NotIncluded,
+}
+
+impl fmt::Display for WalkSkip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalkSkip::Hidden(name) => write!(f, "hidden path component '{name}'"),
+            WalkSkip::Ignored { pattern, source } => {
+                write!(f, "ignored by pattern '{pattern}' in {}", source.display())
+            }
+            WalkSkip::ExcludedDir(name) => write!(f, "inside excluded directory '{name}'"),
+            WalkSkip::Excluded => write!(f, "matches an --exclude glob"),
+            WalkSkip::NotIncluded => write!(f, "matches no --include glob"),
+        }
+    }
+}
+
+/// Explain why walking `root` would not yield `path`, or `None` if it would be yielded.
+///
+/// Mirrors the walker's rules: excluded directories, hidden components, ignore
+/// files (gitignore only inside a git repository), then include and exclude globs.
+pub fn explain_walk_skip(root: &Path, path: &Path, options: &WalkOptions) -> Option<WalkSkip> {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let relative = path.strip_prefix(&root).unwrap_or(&path);
+    let names: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+
+    // Directories between the root and the file
+    let dir_count = names.len().saturating_sub(1);
+    if let Some(name) = names[..dir_count]
+        .iter()
+        .find(|name| options.exclude_dirs.is_match(name.as_str()))
+    {
+        return Some(WalkSkip::ExcludedDir(name.clone()));
+    }
+    if let Some(name) = names.iter().find(|name| name.starts_with('.')) {
+        return Some(WalkSkip::Hidden(name.clone()));
+    }
+    if let Some(skip) = ignore_file_match(&path) {
+        return Some(skip);
+    }
+    if !options.includes.is_empty() && !matches_globset(&options.includes, &root, &path) {
+        return Some(WalkSkip::NotIncluded);
+    }
+    if matches_globset(&options.excludes, &root, &path) {
+        return Some(WalkSkip::Excluded);
+    }
+    None
+}
+
+/// Find the ignore-file pattern that applies to `path`, nearest directory first.
+fn ignore_file_match(path: &Path) -> Option<WalkSkip> {
+    let is_dir = path.is_dir();
+    let git_root = path.ancestors().skip(1).find(|d| d.join(".git").exists());
+
+    for dir in path.ancestors().skip(1) {
+        // Same precedence as the walker: .ignore over .gitignore over .git/info/exclude
+        let mut sources = vec![dir.join(".ignore")];
+        if git_root.is_some() {
+            sources.push(dir.join(".gitignore"));
+        }
+        if Some(dir) == git_root {
+            sources.push(dir.join(".git/info/exclude"));
+        }
+
+        for source in sources.into_iter().filter(|s| s.is_file()) {
+            let mut builder = GitignoreBuilder::new(dir);
+            builder.add(&source);
+            let Ok(gitignore) = builder.build() else {
+                continue;
+            };
+            match gitignore.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(glob) => {
+                    return Some(WalkSkip::Ignored {
+                        pattern: glob.original().to_string(),
+                        source,
+                    })
+                }
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+
+        // Ignore files above the repository don't apply to it
+        if Some(dir) == git_root {
+            break;
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,4 +393,316 @@ mod tests {
             .iter()
             .any(|f| f.to_string_lossy().contains("kept.txt")));
     }
+
+    #[test]
+    fn test_exclude_glob_skips_matching_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("Cargo.lock"), "lock").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/other.lock"), "lock").unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let options = WalkOptions {
+            excludes: build_globset(&["*.lock".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+        let files: Vec<_> = walk_paths_with(&paths, &options)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].to_string_lossy().contains("main.rs"));
+    }
+
+    #[test]
+    fn test_include_glob_limits_files() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "source").unwrap();
+        fs::write(dir.path().join("src/gen.rs"), "generated").unwrap();
+        fs::write(dir.path().join("README.md"), "docs").unwrap();
+
+        let options = WalkOptions {
+            includes: build_globset(&["*.rs".to_string()]).unwrap(),
+            excludes: build_globset(&["gen.rs".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+        let files: Vec<_> = walk_paths_with(&paths, &options)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // Excludes still win over includes
+        assert_eq!(files, [dir.path().join("src/lib.rs")]);
+        assert_eq!(
+            explain_walk_skip(dir.path(), &dir.path().join("README.md"), &options),
+            Some(WalkSkip::NotIncluded)
+        );
+    }
+
+    #[test]
+    fn test_exclude_glob_matches_relative_path() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("gen")).unwrap();
+        fs::write(dir.path().join("gen/out.rs"), "generated").unwrap();
+        fs::write(dir.path().join("lib.rs"), "source").unwrap();
+
+        let options = WalkOptions {
+            excludes: build_globset(&["gen/*".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+        let files: Vec<_> = walk_paths_with(&paths, &options)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].to_string_lossy().contains("lib.rs"));
+    }
+
+    #[test]
+    fn test_glob_candidate_normalizes_windows_separators() {
+        assert_eq!(
+            glob_candidate(Path::new(r"C:\proj"), Path::new(r"C:\proj\src\foo.rs")),
+            "src/foo.rs"
+        );
+        assert_eq!(
+            glob_candidate(Path::new("."), Path::new(r".\src\foo.rs")),
+            "src/foo.rs"
+        );
+        assert_eq!(
+            glob_candidate(Path::new("proj"), Path::new("proj/src/foo.rs")),
+            "src/foo.rs"
+        );
+    }
+
+    #[test]
+    fn test_glob_candidate_handles_unc_root() {
+        assert_eq!(
+            glob_candidate(
+                Path::new(r"\\server\share\proj"),
+                Path::new(r"\\server\share\proj\gen\out.rs")
+            ),
+            "gen/out.rs"
+        );
+        assert_eq!(
+            glob_candidate(
+                Path::new(r"\\server\share\"),
+                Path::new(r"\\server\share\a.rs")
+            ),
+            "a.rs"
+        );
+    }
+
+    #[test]
+    fn test_glob_candidate_outside_root_keeps_full_path() {
+        // A sibling whose name merely starts with the root's name isn't inside it
+        assert_eq!(
+            glob_candidate(Path::new("src"), Path::new("srcgen/a.rs")),
+            "srcgen/a.rs"
+        );
+    }
+
+    #[test]
+    fn test_exclude_matches_backslash_paths() {
+        let globs = build_globset(&["src/*.rs".to_string()]).unwrap();
+        assert!(matches_globset(
+            &globs,
+            Path::new(r"C:\proj"),
+            Path::new(r"C:\proj\src\foo.rs")
+        ));
+        assert!(!matches_globset(
+            &globs,
+            Path::new(r"C:\proj"),
+            Path::new(r"C:\proj\tests\foo.rs")
+        ));
+    }
+
+    #[test]
+    fn test_read_patterns_file_skips_comments_and_blanks() {
+        let dir = TempDir::new().unwrap();
+        let patterns_path = dir.path().join("excludes");
+        fs::write(&patterns_path, "# lock files\n*.lock\n\n  dist/*  \n").unwrap();
+
+        let patterns = read_patterns_file(&patterns_path).unwrap();
+        assert_eq!(patterns, vec!["*.lock".to_string(), "dist/*".to_string()]);
+    }
+
+    #[test]
+    fn test_build_globset_rejects_invalid_pattern() {
+        let result = build_globset(&["[unclosed".to_string()]);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_exclude_dir_prunes_subtree() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("build/deep/deeper")).unwrap();
+        fs::write(dir.path().join("build/deep/deeper/sentinel.txt"), "x").unwrap();
+        fs::create_dir_all(dir.path().join("src/build")).unwrap();
+        fs::write(dir.path().join("src/build/nested.txt"), "x").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "x").unwrap();
+
+        let options = WalkOptions {
+            exclude_dirs: build_globset(&["build".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+        let files: Vec<_> = walk_paths_with(&paths, &options)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("src/main.rs"));
+    }
+
+    #[test]
+    fn test_exclude_dir_does_not_prune_root() {
+        let dir = TempDir::new().unwrap();
+        let build = dir.path().join("build");
+        fs::create_dir(&build).unwrap();
+        fs::write(build.join("file.txt"), "x").unwrap();
+
+        let options = WalkOptions {
+            exclude_dirs: build_globset(&["build".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let paths = vec![build.to_string_lossy().to_string()];
+        let files: Vec<_> = walk_paths_with(&paths, &options)
+            .filter_map(|r| r.ok())
+            .collect();
+
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_walk_error_keeps_missing_path() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing");
+        let paths = vec![missing.to_string_lossy().to_string()];
+
+        let errors: Vec<_> = walk_paths(&paths).filter_map(|r| r.err()).collect();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "No such file or directory");
+        assert_eq!(errors[0].path.as_deref(), Some(missing.as_path()));
+        assert!(errors[0]
+            .to_string()
+            .starts_with(&missing.display().to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_error_names_unreadable_subdir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(dir.path().join("ok.txt"), "ok").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permission bits don't apply to root; nothing to test then
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let paths = vec![dir.path().to_string_lossy().to_string()];
+        let results: Vec<_> = walk_paths(&paths).collect();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let error = results.iter().find_map(|r| r.as_ref().err()).unwrap();
+        assert_eq!(error.path.as_deref(), Some(locked.as_path()));
+        assert!(results.iter().any(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_overlapping_paths_yield_each_file_once() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("top.txt"), "top").unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "lib").unwrap();
+
+        let src = dir.path().join("src");
+        let paths = vec![
+            dir.path().to_string_lossy().to_string(),
+            src.to_string_lossy().to_string(),
+            src.join("lib.rs").to_string_lossy().to_string(),
+        ];
+        let files: Vec<_> = walk_paths(&paths).filter_map(|r| r.ok()).collect();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(
+            files.iter().filter(|f| f.ends_with("src/lib.rs")).count(),
+            1
+        );
+    }
+
+    // ===========================================
+    // Skip Explanations
+    // ===========================================
+
+    #[test]
+    fn test_explain_gitignored_file() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        let file = dir.path().join("debug.log");
+        fs::write(&file, "log").unwrap();
+
+        let skip = explain_walk_skip(dir.path(), &file, &WalkOptions::default());
+
+        assert_eq!(
+            skip,
+            Some(WalkSkip::Ignored {
+                pattern: "*.log".to_string(),
+                source: dir.path().join(".gitignore"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_explain_gitignore_needs_git_repo() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        let file = dir.path().join("debug.log");
+        fs::write(&file, "log").unwrap();
+
+        assert_eq!(
+            explain_walk_skip(dir.path(), &file, &WalkOptions::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_explain_hidden_and_excluded() {
+        let dir = TempDir::new().unwrap();
+        let hidden = dir.path().join(".config/settings.txt");
+        let built = dir.path().join("target/out.txt");
+        let generated = dir.path().join("schema.gen.rs");
+        let options = WalkOptions {
+            excludes: build_globset(&["*.gen.rs".to_string()]).unwrap(),
+            exclude_dirs: build_globset(&["target".to_string()]).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            explain_walk_skip(dir.path(), &hidden, &options),
+            Some(WalkSkip::Hidden(".config".to_string()))
+        );
+        assert_eq!(
+            explain_walk_skip(dir.path(), &built, &options),
+            Some(WalkSkip::ExcludedDir("target".to_string()))
+        );
+        assert_eq!(
+            explain_walk_skip(dir.path(), &generated, &options),
+            Some(WalkSkip::Excluded)
+        );
+        assert_eq!(
+            explain_walk_skip(dir.path(), &dir.path().join("main.rs"), &options),
+            None
+        );
+    }
 }